@@ -1,149 +1,1556 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+use std::hash::Hash;
 
-pub struct ASimpleGraph {
+use crate::union_find::UnionFind;
+
+mod biconnected;
+mod centrality;
+mod coloring;
+mod csr;
+mod euler;
+pub mod generate;
+mod io;
+mod paths;
+mod reach;
+pub use self::coloring::ColoringOrder;
+pub use self::csr::CsrGraph;
+pub use self::euler::EulerError;
+pub use self::io::ParseError;
+pub use self::reach::Reachability;
+
+#[derive(Debug)]
+pub struct ASimpleGraph<K> {
     adjacency_list: Vec<Vec<Edge>>,
-    nodes: Vec<KeyType>
+    nodes: Vec<K>,
+    //Reverse lookup of nodes, every key maps to its position in nodes (and adjacency_list)
+    index: HashMap<K, usize>,
+    kind: GraphKind,
+    multigraph: bool,
 }
 //Makes sense to keep the actual values, identifiers or actual objects
 //In their own list and simply work with indices of the usize type
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 struct Edge {
     weight: u32,
-    node:usize,
-}
-
-fn get_node_index(&self, node: KeyType) -> Option<usize> {
-    self.nodes.iter().position(|n| n == &node)
-}
-//Check for valid node. Looks up the IDs provided in edges parameter to find the index
-pub fn set_edges(&mut self, from: KeyType, edges: Vec<(u32, KeyType)>) {
-    //Filter_map of the iterator will only include elements that evaluate to Some()
-    let edges: Vec<Edge> = edges.into_iter().filter_map(|e| {
-	if let Some(to) = self.get_node_index(e.1){
-	    Some(Edge {weight: e.0, node: to})
-	} else {
-	    None
-	}}).collect();
-    //Find index and ID through position() function of iterator trait
-    match self.nodes.iter().position(|n| n == &from) {
-	Some(i) => self.adjacency_list[i] = edges,
-	None => {
-	    self.nodes.push(from);
-	    self.adjacency_list(edges)
-	}
-    }
-}
-
-pub fn set_nodes(&mut self, nodes: Vec<KeyType>){
-    self.nodes = nodes;
-    self.adjacency_list = vec![vec![]; self.node.len()]
-}
-//Takes care of validating user input and sees if node actually exists
-pub fn connected(&self, from: KeyType, degree: usize) -> Option<HashSet<KeyType>> {
-    self.nodes.iter().position(|n| n == &from).map(|i| {
-	self.connected_r(i, degree).into_iter().map(|n|
-	self.nodes[n].clone()).collect()
-    })
+    node: usize,
+    label: Option<String>,
+}
+
+//An undirected graph stores every edge in the lists of both of its ends, so all
+//the algorithms can keep walking adjacency_list without caring about the kind.
+//Self loops are the exception, they are only stored once
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GraphKind {
+    #[default]
+    Directed,
+    Undirected,
+}
+
+//Dijkstra needs a weight that can be "not reached yet". Deriving the ordering
+//puts every Number before Infinite, so comparisons work out of the box
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+enum TentativeWeight {
+    Number(u32),
+    Infinite,
+}
+
+//Picks the position (within open) of the node with the lowest tentative weight
+fn min_index(weights: &[TentativeWeight], nodes: &[usize]) -> usize {
+    let mut min_weight = (weights[nodes[0]].clone(), 0);
+    for (i, n) in nodes.iter().enumerate().skip(1) {
+        if weights[*n] < min_weight.0 {
+            min_weight = (weights[*n].clone(), i);
+        }
+    }
+    min_weight.1
+}
+
+impl<K: Clone + Eq + Hash> Default for ASimpleGraph<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone + Eq + Hash> ASimpleGraph<K> {
+    pub fn new() -> Self {
+        Self::with_kind(GraphKind::Directed)
+    }
+
+    //A simple graph has at most one edge from a node to another, adding it again
+    //only updates the weight
+    pub fn with_kind(kind: GraphKind) -> Self {
+        ASimpleGraph {
+            adjacency_list: vec![],
+            nodes: vec![],
+            index: HashMap::new(),
+            kind,
+            multigraph: false,
+        }
+    }
+
+    //A multigraph allows parallel edges between the same nodes, as long as their
+    //labels differ. Adding an edge with a label that is already there updates the weight
+    pub fn multigraph(kind: GraphKind) -> Self {
+        ASimpleGraph {
+            multigraph: true,
+            ..Self::with_kind(kind)
+        }
+    }
+
+    pub fn kind(&self) -> GraphKind {
+        self.kind
+    }
+
+    pub fn is_multigraph(&self) -> bool {
+        self.multigraph
+    }
+
+    fn get_node_index(&self, node: &K) -> Option<usize> {
+        self.index.get(node).cloned()
+    }
+
+    //Index of the node, which is added without edges if it isn't in the graph yet
+    fn insert_node(&mut self, node: K) -> usize {
+        if let Some(i) = self.get_node_index(&node) {
+            return i;
+        }
+        let i = self.nodes.len();
+        self.index.insert(node.clone(), i);
+        self.nodes.push(node);
+        self.adjacency_list.push(vec![]);
+        i
+    }
+
+    //Position of the edge that (from, to, label) would collide with. In a simple graph
+    //that's any edge between the two, in a multigraph only one with the same label
+    fn find_edge(&self, from: usize, to: usize, label: &Option<String>) -> Option<usize> {
+        self.adjacency_list[from]
+            .iter()
+            .position(|e| e.node == to && (!self.multigraph || &e.label == label))
+    }
+
+    //Every edge goes through here, it mirrors edges of undirected graphs and keeps
+    //simple graphs simple. Returns false if an existing edge was updated instead
+    fn push_edge(&mut self, from: usize, to: usize, weight: u32, label: Option<String>) -> bool {
+        let mirrored = self.kind == GraphKind::Undirected && from != to;
+        if let Some(p) = self.find_edge(from, to, &label) {
+            let old_label = self.adjacency_list[from][p].label.clone();
+            self.adjacency_list[from][p].weight = weight;
+            self.adjacency_list[from][p].label = label.clone();
+            if mirrored {
+                if let Some(p) = self.find_edge(to, from, &old_label) {
+                    self.adjacency_list[to][p].weight = weight;
+                    self.adjacency_list[to][p].label = label;
+                }
+            }
+            return false;
+        }
+        if mirrored {
+            self.adjacency_list[to].push(Edge {
+                weight,
+                node: from,
+                label: label.clone(),
+            });
+        }
+        self.adjacency_list[from].push(Edge {
+            weight,
+            node: to,
+            label,
+        });
+        true
+    }
+
+    //Takes the edge at position p out of the list of from, along with its mirror
+    fn take_edge(&mut self, from: usize, p: usize) -> Edge {
+        let edge = self.adjacency_list[from].remove(p);
+        if self.kind == GraphKind::Undirected && edge.node != from {
+            let mirror = self.adjacency_list[edge.node]
+                .iter()
+                .position(|e| e.node == from && e.label == edge.label);
+            if let Some(m) = mirror {
+                self.adjacency_list[edge.node].remove(m);
+            }
+        }
+        edge
+    }
+
+    //Check for valid node. Looks up the IDs provided in edges parameter to find the index
+    //Replaces all edges of from, in an undirected graph that includes the mirrored ones
+    pub fn set_edges(&mut self, from: K, edges: Vec<(u32, K)>) {
+        //Filter_map of the iterator will only include elements that evaluate to Some()
+        let edges: Vec<(u32, usize)> = edges
+            .into_iter()
+            .filter_map(|e| self.get_node_index(&e.1).map(|to| (e.0, to)))
+            .collect();
+        let i = self.insert_node(from);
+        while !self.adjacency_list[i].is_empty() {
+            self.take_edge(i, 0);
+        }
+        for (weight, to) in edges {
+            self.push_edge(i, to, weight, None);
+        }
+    }
+
+    //Outgoing edges of a node as (weight, to, label), all edges touching it if undirected
+    pub fn edges(&self, from: K) -> Option<Vec<(u32, K, Option<&str>)>> {
+        let i = self.get_node_index(&from)?;
+        Some(
+            self.adjacency_list[i]
+                .iter()
+                .map(|e| (e.weight, self.nodes[e.node].clone(), e.label.as_deref()))
+                .collect(),
+        )
+    }
+
+    pub fn set_nodes(&mut self, nodes: Vec<K>) {
+        self.index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.clone(), i))
+            .collect();
+        self.nodes = nodes;
+        self.adjacency_list = vec![vec![]; self.nodes.len()]
+    }
+
+    //Adds an edge next to the existing ones, both nodes are added if they are missing.
+    //Returns false if the edge was already there and only got its weight updated
+    pub fn add_edge(&mut self, from: K, to: K, weight: u32) -> bool {
+        let from = self.insert_node(from);
+        let to = self.insert_node(to);
+        self.push_edge(from, to, weight, None)
+    }
+
+    pub fn add_labeled_edge(&mut self, from: K, to: K, weight: u32, label: &str) -> bool {
+        let from = self.insert_node(from);
+        let to = self.insert_node(to);
+        self.push_edge(from, to, weight, Some(label.to_string()))
+    }
+
+    //Removes the first edge from -> to and returns its weight
+    pub fn remove_edge(&mut self, from: K, to: K) -> Option<u32> {
+        let (from, to) = (self.get_node_index(&from)?, self.get_node_index(&to)?);
+        let p = self.adjacency_list[from]
+            .iter()
+            .position(|e| e.node == to)?;
+        Some(self.take_edge(from, p).weight)
+    }
+
+    pub fn remove_labeled_edge(&mut self, from: K, to: K, label: &str) -> Option<u32> {
+        let (from, to) = (self.get_node_index(&from)?, self.get_node_index(&to)?);
+        let p = self.adjacency_list[from]
+            .iter()
+            .position(|e| e.node == to && e.label.as_deref() == Some(label))?;
+        Some(self.take_edge(from, p).weight)
+    }
+
+    //Removes the node along with every edge into or out of it. The last node is
+    //swapped into the freed slot, so only edges pointing at the last node need fixing up.
+    //O(V + E), but indices stay dense and no other node has to move
+    pub fn remove_node(&mut self, node: K) -> bool {
+        let i = match self.index.remove(&node) {
+            Some(i) => i,
+            None => return false,
+        };
+        let last = self.nodes.len() - 1;
+        self.nodes.swap_remove(i);
+        self.adjacency_list.swap_remove(i);
+        for edges in &mut self.adjacency_list {
+            edges.retain(|e| e.node != i);
+            for e in edges.iter_mut() {
+                if e.node == last {
+                    e.node = i;
+                }
+            }
+        }
+        if i != last {
+            self.index.insert(self.nodes[i].clone(), i);
+        }
+        true
+    }
+}
+
+//Every GraphView query is an inherent method as well, so callers don't need the trait
+//in scope. A new query goes in both places
+impl<K: Clone + Eq + Hash> ASimpleGraph<K> {
+    pub fn connected(&self, from: K, degree: usize) -> Option<HashSet<K>> {
+        GraphView::connected(self, from, degree)
+    }
+
+    pub fn shortest_path(&self, from: K, to: K) -> Option<(u32, Vec<K>)> {
+        GraphView::shortest_path(self, from, to)
+    }
+
+    pub fn floyd_warshall(&self) -> DistanceMatrix<K> {
+        GraphView::floyd_warshall(self)
+    }
+
+    pub fn johnson(&self) -> DistanceMatrix<K> {
+        GraphView::johnson(self)
+    }
+
+    pub fn kruskal(&self) -> (u32, Vec<(K, K, u32)>) {
+        GraphView::kruskal(self)
+    }
+
+    pub fn prim(&self) -> (u32, Vec<(K, K, u32)>) {
+        GraphView::prim(self)
+    }
+
+    pub fn strongly_connected_components(&self) -> Vec<HashSet<K>> {
+        GraphView::strongly_connected_components(self)
+    }
+
+    pub fn condensation(&self) -> (ASimpleGraph<usize>, Vec<HashSet<K>>) {
+        GraphView::condensation(self)
+    }
+
+    pub fn max_flow(&self, source: K, sink: K) -> Option<MaxFlow<K>> {
+        GraphView::max_flow(self, source, sink)
+    }
+
+    pub fn bipartite_matching(&self, left: &HashSet<K>) -> Vec<(K, K)> {
+        GraphView::bipartite_matching(self, left)
+    }
+
+    pub fn in_degree_centrality(&self) -> HashMap<K, f64> {
+        GraphView::in_degree_centrality(self)
+    }
+
+    pub fn out_degree_centrality(&self) -> HashMap<K, f64> {
+        GraphView::out_degree_centrality(self)
+    }
+
+    pub fn betweenness_centrality(&self) -> HashMap<K, f64> {
+        GraphView::betweenness_centrality(self)
+    }
+
+    pub fn closeness_centrality(&self) -> HashMap<K, f64> {
+        GraphView::closeness_centrality(self)
+    }
+
+    pub fn pagerank(&self, damping: f64, tolerance: f64) -> HashMap<K, f64> {
+        GraphView::pagerank(self, damping, tolerance)
+    }
+
+    pub fn articulation_points(&self) -> HashSet<K> {
+        GraphView::articulation_points(self)
+    }
+
+    pub fn bridges(&self) -> Vec<(K, K)> {
+        GraphView::bridges(self)
+    }
+
+    pub fn biconnected_components(&self) -> Vec<HashSet<K>> {
+        GraphView::biconnected_components(self)
+    }
+
+    pub fn k_shortest_paths(&self, from: K, to: K, k: usize) -> Vec<(u32, Vec<K>)> {
+        GraphView::k_shortest_paths(self, from, to, k)
+    }
+
+    pub fn bidirectional_shortest_path(&self, from: K, to: K) -> Option<(u32, Vec<K>)> {
+        GraphView::bidirectional_shortest_path(self, from, to)
+    }
+
+    pub fn is_bipartite(&self) -> Result<(HashSet<K>, HashSet<K>), Vec<K>> {
+        GraphView::is_bipartite(self)
+    }
+
+    pub fn greedy_coloring(&self, order: ColoringOrder) -> HashMap<K, usize> {
+        GraphView::greedy_coloring(self, order)
+    }
+
+    pub fn eulerian_path(&self) -> Result<Vec<K>, EulerError<K>> {
+        GraphView::eulerian_path(self)
+    }
+
+    pub fn eulerian_circuit(&self) -> Result<Vec<K>, EulerError<K>> {
+        GraphView::eulerian_circuit(self)
+    }
+
+    pub fn reachability(&self) -> Reachability<K> {
+        GraphView::reachability(self)
+    }
+
+    pub fn reachability_within(&self, max_bytes: usize) -> Reachability<K> {
+        GraphView::reachability_within(self, max_bytes)
+    }
+}
+
+impl<K: Clone + Eq + Hash> GraphView<K> for ASimpleGraph<K> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&self, i: usize) -> &K {
+        &self.nodes[i]
+    }
+
+    fn node_index(&self, node: &K) -> Option<usize> {
+        self.get_node_index(node)
+    }
+
+    fn neighbors(&self, i: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        self.adjacency_list[i].iter().map(|e| (e.node, e.weight))
+    }
+
+    fn kind(&self) -> GraphKind {
+        self.kind
+    }
 }
+
+//Read-only view of a graph: nodes are the dense indices 0..node_count(), each with a
+//key and a list of weighted edges. Every query algorithm is written once against it,
+//so ASimpleGraph and its frozen CsrGraph get all of them.
+//Undirected graphs list every edge from both of its ends. Path costs are u32 like the
+//weights, a path that would cost more than u32::MAX counts as not there
+pub trait GraphView<K: Clone + Eq + Hash> {
+    fn node_count(&self) -> usize;
+    fn node(&self, i: usize) -> &K;
+    fn node_index(&self, node: &K) -> Option<usize>;
+    //(to, weight) for every edge leaving i
+    fn neighbors(&self, i: usize) -> impl Iterator<Item = (usize, u32)> + '_;
+    fn kind(&self) -> GraphKind;
+
+    //Takes care of validating user input and sees if node actually exists
+    fn connected(&self, from: K, degree: usize) -> Option<HashSet<K>> {
+        self.node_index(&from).map(|i| {
+            connected_r(self, i, degree)
+                .into_iter()
+                .map(|n| self.node(n).clone())
+                .collect()
+        })
+    }
+
+    fn shortest_path(&self, from: K, to: K) -> Option<(u32, Vec<K>)> {
+        //Boiler-plate code to ensure that both source and destination nodes are nodes in graph
+        let src = self.node_index(&from);
+        let dest = self.node_index(&to);
+        if let (Some(src), Some(dest)) = (src, dest) {
+            //Each node gets a tentative weight assigned, which is infinite in beginning
+            //Except for orgin node, which has zero cost to reach
+            let mut distance: Vec<TentativeWeight> =
+                vec![TentativeWeight::Infinite; self.node_count()];
+            distance[src] = TentativeWeight::Number(0);
+            //"Open" list contains all the nodes yet to be processed, is created using Rust's range, as it corrosponds to the indices we are working with/
+            let mut open: Vec<usize> = (0..self.node_count()).collect();
+            //Parent array keeps track of each node's parent once the lower cost is established allows to trace back the best possible path
+            let mut parent = vec![None; self.node_count()];
+            let mut found = false;
+            while !open.is_empty() {
+                //min_index is a helper function takes the current distance and returns the index of the node that is the easiest to reach next
+                let u = min_index(&distance, &open);
+                //This node is then removed from the open list
+                let u = open.remove(u);
+                //Everything left in open is unreachable, no point in going on
+                if distance[u] == TentativeWeight::Infinite {
+                    break;
+                }
+                //Stop if dest is reached
+                if u == dest {
+                    found = true;
+                    break;
+                }
+                let dist = distance[u].clone();
+                //for each edge of this node, the new distance is computed and if lower, inserted into a distance list
+                for (node, weight) in self.neighbors(u) {
+                    let new_distance = match dist {
+                        TentativeWeight::Number(n) => n
+                            .checked_add(weight)
+                            .map_or(TentativeWeight::Infinite, TentativeWeight::Number),
+                        _ => TentativeWeight::Infinite,
+                    };
+                    let old_distance = distance[node].clone();
+
+                    if new_distance < old_distance {
+                        distance[node] = new_distance;
+                        parent[node] = Some(u);
+                    }
+                }
+            }
+            //Distance array and a parent array to be prepared for returning to the caller
+            if found {
+                //Trace back the path from the destination to the orgin node in the parent array, leads to the reverse optimal path between the two nodes
+                let mut path = vec![self.node(dest).clone()];
+                let mut p = dest;
+                while p != src {
+                    p = parent[p].unwrap();
+                    path.push(self.node(p).clone());
+                }
+
+                path.reverse();
+                let cost = match distance[dest] {
+                    TentativeWeight::Number(n) => n,
+                    _ => 0,
+                };
+                Some((cost, path))
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    //Floyd-Warshall relaxes every pair (i, j) through every intermediate node k.
+    //O(V^3) time and O(V^2) space, which is fine for dense graphs
+    fn floyd_warshall(&self) -> DistanceMatrix<K> {
+        let n = self.node_count();
+        let mut distance = vec![vec![None; n]; n];
+        //parent[i][j] is the node right before j on the best path from i
+        let mut parent = vec![vec![None; n]; n];
+        for i in 0..n {
+            distance[i][i] = Some(0);
+            for (node, weight) in self.neighbors(i) {
+                if node != i && distance[i][node].is_none_or(|old| weight < old) {
+                    distance[i][node] = Some(weight);
+                    parent[i][node] = Some(i);
+                }
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                let Some(ik) = distance[i][k] else { continue };
+                for j in 0..n {
+                    let Some(kj) = distance[k][j] else { continue };
+                    let Some(through_k) = ik.checked_add(kj) else {
+                        continue;
+                    };
+                    if distance[i][j].is_none_or(|old| through_k < old) {
+                        distance[i][j] = Some(through_k);
+                        parent[i][j] = parent[k][j];
+                    }
+                }
+            }
+        }
+        DistanceMatrix::new(keys(self), distance, parent)
+    }
+
+    //Johnson's algorithm runs Dijkstra from every node, O(V E log V), which beats
+    //Floyd-Warshall on sparse graphs. The Bellman-Ford reweighting step that makes
+    //negative edges safe for Dijkstra is a no-op here, since weights are unsigned
+    fn johnson(&self) -> DistanceMatrix<K> {
+        let (distance, parent) = (0..self.node_count()).map(|i| dijkstra(self, i)).unzip();
+        DistanceMatrix::new(keys(self), distance, parent)
+    }
+
+    //Kruskal sorts all edges by weight and takes every edge that joins two different
+    //trees of the forest. The union-find answers "same tree?" in near constant time.
+    //Disconnected graphs give a minimum spanning forest
+    fn kruskal(&self) -> (u32, Vec<(K, K, u32)>) {
+        let mut edges: Vec<(u32, usize, usize)> = (0..self.node_count())
+            .flat_map(|from| {
+                self.neighbors(from)
+                    .map(move |(to, weight)| (weight, from, to))
+            })
+            .collect();
+        edges.sort_unstable();
+        let mut sets = UnionFind::new(self.node_count());
+        let mut total = 0;
+        let mut tree = vec![];
+        for (weight, from, to) in edges {
+            if sets.union(from, to) {
+                total += weight;
+                tree.push((self.node(from).clone(), self.node(to).clone(), weight));
+            }
+        }
+        (total, tree)
+    }
+
+    //Prim grows one tree at a time, always taking the cheapest edge leaving it.
+    //Whenever the heap runs dry the next unvisited node starts a new tree of the forest
+    fn prim(&self) -> (u32, Vec<(K, K, u32)>) {
+        let adjacency = undirected_adjacency(self);
+        let mut visited = vec![false; self.node_count()];
+        let mut total = 0;
+        let mut tree = vec![];
+        for root in 0..self.node_count() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut heap: BinaryHeap<Reverse<(u32, usize, usize)>> = adjacency[root]
+                .iter()
+                .map(|&(node, weight)| Reverse((weight, root, node)))
+                .collect();
+            while let Some(Reverse((weight, from, to))) = heap.pop() {
+                if visited[to] {
+                    continue;
+                }
+                visited[to] = true;
+                total += weight;
+                tree.push((self.node(from).clone(), self.node(to).clone(), weight));
+                heap.extend(
+                    adjacency[to]
+                        .iter()
+                        .filter(|&&(node, _)| !visited[node])
+                        .map(|&(node, weight)| Reverse((weight, to, node))),
+                );
+            }
+        }
+        (total, tree)
+    }
+
+    //Every edge leads either within a component or "downwards" in the returned order
+    fn strongly_connected_components(&self) -> Vec<HashSet<K>> {
+        scc_indices(self)
+            .into_iter()
+            .map(|c| c.into_iter().map(|n| self.node(n).clone()).collect())
+            .collect()
+    }
+
+    //Collapses every strongly connected component into a single node. Node i of the
+    //returned DAG is component i, edges between components keep the cheapest weight
+    fn condensation(&self) -> (ASimpleGraph<usize>, Vec<HashSet<K>>) {
+        let components = scc_indices(self);
+        let mut component_of = vec![0; self.node_count()];
+        for (c, members) in components.iter().enumerate() {
+            for &n in members {
+                component_of[n] = c;
+            }
+        }
+        let mut edges: Vec<HashMap<usize, u32>> = vec![HashMap::new(); components.len()];
+        for from in 0..self.node_count() {
+            for (to, w) in self.neighbors(from) {
+                let (cf, ct) = (component_of[from], component_of[to]);
+                if cf != ct {
+                    let weight = edges[cf].entry(ct).or_insert(w);
+                    *weight = (*weight).min(w);
+                }
+            }
+        }
+        let mut dag = ASimpleGraph::new();
+        dag.set_nodes((0..components.len()).collect());
+        for (c, targets) in edges.into_iter().enumerate() {
+            let mut targets: Vec<(u32, usize)> = targets.into_iter().map(|(t, w)| (w, t)).collect();
+            targets.sort_unstable_by_key(|&(_, t)| t);
+            dag.set_edges(c, targets);
+        }
+        let components = components
+            .into_iter()
+            .map(|c| c.into_iter().map(|n| self.node(n).clone()).collect())
+            .collect();
+        (dag, components)
+    }
+
+    //Dinic's algorithm, treating every edge weight as a capacity. Returns None if either
+    //node is missing or source and sink are the same node
+    fn max_flow(&self, source: K, sink: K) -> Option<MaxFlow<K>> {
+        let (s, t) = (self.node_index(&source)?, self.node_index(&sink)?);
+        if s == t {
+            return None;
+        }
+        let mut network = FlowNetwork::new(self.node_count());
+        let mut ids = vec![];
+        for from in 0..self.node_count() {
+            for (to, weight) in self.neighbors(from) {
+                ids.push((network.add_edge(from, to, weight), from, to));
+            }
+        }
+        let value = network.max_flow(s, t);
+        let flows = ids
+            .into_iter()
+            .map(|(id, from, to)| {
+                let (from, to) = (self.node(from).clone(), self.node(to).clone());
+                (from, to, network.flow(id))
+            })
+            .collect();
+        //Whatever the source can still reach in the residual network is its side of the min cut
+        let reachable = network.reachable(s);
+        let (source_side, sink_side) = (0..self.node_count())
+            .map(|n| (reachable[n], self.node(n).clone()))
+            .partition::<Vec<_>, _>(|&(r, _)| r);
+        Some(MaxFlow {
+            value,
+            flows,
+            source_side: source_side.into_iter().map(|(_, n)| n).collect(),
+            sink_side: sink_side.into_iter().map(|(_, n)| n).collect(),
+        })
+    }
+
+    //Maximum matching between the nodes in left and every other node, edges are used in
+    //either direction and weights are ignored. Runs Dinic on a unit capacity network with
+    //an extra source feeding the left side and an extra sink draining the right side
+    fn bipartite_matching(&self, left: &HashSet<K>) -> Vec<(K, K)> {
+        let n = self.node_count();
+        let (s, t) = (n, n + 1);
+        let is_left: Vec<bool> = (0..n).map(|i| left.contains(self.node(i))).collect();
+        let mut network = FlowNetwork::new(n + 2);
+        let mut candidates = vec![];
+        for from in 0..n {
+            for (to, _) in self.neighbors(from) {
+                let pair = match (is_left[from], is_left[to]) {
+                    (true, false) => (from, to),
+                    (false, true) => (to, from),
+                    _ => continue,
+                };
+                candidates.push((network.add_edge(pair.0, pair.1, 1), pair));
+            }
+        }
+        for (i, &l) in is_left.iter().enumerate() {
+            if l {
+                network.add_edge(s, i, 1);
+            } else {
+                network.add_edge(i, t, 1);
+            }
+        }
+        network.max_flow(s, t);
+        candidates
+            .into_iter()
+            .filter(|&(id, _)| network.flow(id) > 0)
+            .map(|(_, (l, r))| (self.node(l).clone(), self.node(r).clone()))
+            .collect()
+    }
+    //Number of edges coming into every node, divided by the n - 1 other nodes
+    fn in_degree_centrality(&self) -> HashMap<K, f64> {
+        centrality::degree(self, true)
+    }
+
+    fn out_degree_centrality(&self) -> HashMap<K, f64> {
+        centrality::degree(self, false)
+    }
+
+    //Brandes' algorithm with weighted shortest paths, O(V E log V).
+    //How many shortest paths between other pairs of nodes run through each node.
+    //Nodes on a cycle of zero weight edges count as one stop: a path through them is
+    //counted once, and each of them gets the credit for it
+    fn betweenness_centrality(&self) -> HashMap<K, f64> {
+        centrality::betweenness(self)
+    }
+
+    //Inverse of the average distance from a node to the nodes it can reach, scaled
+    //down by the share of the graph it can reach (Wasserman and Faust)
+    fn closeness_centrality(&self) -> HashMap<K, f64> {
+        centrality::closeness(self)
+    }
+
+    //Power iteration until the ranks move less than tolerance in total (L1).
+    //Edges hand out rank in proportion to their weight, nodes without outgoing
+    //weight spread theirs over the whole graph
+    fn pagerank(&self, damping: f64, tolerance: f64) -> HashMap<K, f64> {
+        centrality::pagerank(self, damping, tolerance)
+    }
+
+    //Nodes whose removal splits their connected component. Edge direction is ignored
+    fn articulation_points(&self) -> HashSet<K> {
+        let found = biconnected::search(self);
+        (0..self.node_count())
+            .filter(|&i| found.articulation[i])
+            .map(|i| self.node(i).clone())
+            .collect()
+    }
+
+    //Edges whose removal splits their connected component. Edge direction is ignored
+    fn bridges(&self) -> Vec<(K, K)> {
+        biconnected::search(self)
+            .bridges
+            .into_iter()
+            .map(|(u, v)| (self.node(u).clone(), self.node(v).clone()))
+            .collect()
+    }
+
+    //Maximal groups of nodes that stay connected whatever single node is removed.
+    //Articulation points are in more than one group, nodes without edges in none
+    fn biconnected_components(&self) -> Vec<HashSet<K>> {
+        biconnected::search(self)
+            .components
+            .into_iter()
+            .map(|c| c.into_iter().map(|i| self.node(i).clone()).collect())
+            .collect()
+    }
+
+    //Up to k cheapest paths from `from` to `to` that don't visit a node twice,
+    //cheapest first
+    fn k_shortest_paths(&self, from: K, to: K, k: usize) -> Vec<(u32, Vec<K>)> {
+        match (self.node_index(&from), self.node_index(&to)) {
+            (Some(src), Some(dest)) => paths::k_shortest(self, src, dest, k)
+                .into_iter()
+                .map(|(cost, path)| {
+                    (
+                        cost,
+                        path.into_iter().map(|i| self.node(i).clone()).collect(),
+                    )
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    //Same answer as shortest_path, but searches from both ends
+    fn bidirectional_shortest_path(&self, from: K, to: K) -> Option<(u32, Vec<K>)> {
+        let (src, dest) = (self.node_index(&from)?, self.node_index(&to)?);
+        let (cost, path) = paths::bidirectional(self, src, dest)?;
+        Some((
+            cost,
+            path.into_iter().map(|i| self.node(i).clone()).collect(),
+        ))
+    }
+
+    //Splits the nodes in two sides so that every edge goes from one to the other,
+    //or returns the nodes of an odd cycle, in order, proving that can't be done.
+    //Edge direction is ignored
+    fn is_bipartite(&self) -> Result<(HashSet<K>, HashSet<K>), Vec<K>> {
+        match coloring::two_color(self) {
+            Ok(side) => {
+                let (mut left, mut right) = (HashSet::new(), HashSet::new());
+                for (i, right_side) in side.into_iter().enumerate() {
+                    let node = self.node(i).clone();
+                    if right_side {
+                        right.insert(node);
+                    } else {
+                        left.insert(node);
+                    }
+                }
+                Ok((left, right))
+            }
+            Err(cycle) => Err(cycle.into_iter().map(|i| self.node(i).clone()).collect()),
+        }
+    }
+
+    //Colors 0, 1, ... so that no edge joins two nodes of the same color. Greedy, so not
+    //the fewest colors possible, but never more than the highest degree plus one
+    fn greedy_coloring(&self, order: ColoringOrder) -> HashMap<K, usize> {
+        coloring::greedy(self, order)
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| (self.node(i).clone(), c))
+            .collect()
+    }
+
+    //Walk along every edge exactly once, starting and ending anywhere
+    fn eulerian_path(&self) -> Result<Vec<K>, EulerError<K>> {
+        euler::euler(self, false)
+            .map(|path| path.into_iter().map(|i| self.node(i).clone()).collect())
+            .map_err(|e| e.map(self))
+    }
+
+    //Walk along every edge exactly once that ends where it started
+    fn eulerian_circuit(&self) -> Result<Vec<K>, EulerError<K>> {
+        euler::euler(self, true)
+            .map(|path| path.into_iter().map(|i| self.node(i).clone()).collect())
+            .map_err(|e| e.map(self))
+    }
+
+    //Index for repeated "can a reach b" queries, each one a single bit lookup.
+    //Takes O(C^2) bits for C strongly connected components
+    fn reachability(&self) -> Reachability<K> {
+        Reachability::new(self, None)
+    }
+
+    //Same, but falls back to interval labels if the bitsets would take more than
+    //max_bytes. Most queries are still answered by the labels alone
+    fn reachability_within(&self, max_bytes: usize) -> Reachability<K> {
+        Reachability::new(self, Some(max_bytes))
+    }
+}
+
+fn keys<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(graph: &G) -> Vec<K> {
+    (0..graph.node_count())
+        .map(|i| graph.node(i).clone())
+        .collect()
+}
+
 //Recursive call can create a list of all neighbors and run the same call
 //On each. Returning a set of nodes eliminates the duplicates as well
-fn connected_r(&self, from: usize, degree:usize) -> HashSet<usize> {
+fn connected_r<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    from: usize,
+    degree: usize,
+) -> HashSet<usize> {
     if degree > 0 {
-	//Recursive call returns the internal repersentation (indicies)
-	self.adjacency_list[from]
-	    .iter()
-	    .flat_map(|e| {
-		let mut set = self.connected_r(e.node, degree - 1);
-		set.insert(e.node);
-		set
-	    }).collect()
+        //Recursive call returns the internal repersentation (indicies)
+        graph
+            .neighbors(from)
+            .flat_map(|(node, _)| {
+                let mut set = connected_r(graph, node, degree - 1);
+                set.insert(node);
+                set
+            })
+            .collect()
     } else {
-	HashSet::new()
-    }
-}
-                           
-pub fn shortest_path(&self, from: KeyType, to: KeyType) -> Option<(u32, Vec<KeyType>)> {
-    //Boiler-plate code to ensure that both source and destination nodes are nodes in graph
-    let mut src = None;
-    let mut dest = None;
-
-    for(i, n) in self.nodes.iter().enumerate() {
-	if n == &from {
-	    src = Some(i);
-	}
-	if n == &to {
-	    dest = Some(i);
-	}
-	if src.is_some() && dest.is_some() {
-	    break;
-	}
-    }
-    if src.is_some() && dest.is_some() {
-	let (src, dest) = (src.unwrap(), dest.unwrap());
-	//Each node gets a tentative weight assigned, which is infinite in beginning
-	//Except for orgin node, which has zero cost to reach
-	let mut distance: Vec<TentativeWeight> = vec![TentativeWeight::Infinite, self.nodes.len()];
-	distance[src] = TentativeWeight::Number(0);
-	//"Open" list contains all the nodes yet to be processed, is created using Rust's range, as it corrosponds to the indices we are working with/
-	let mut open: Vec<usize> = (0..self.nodes.len()).into_iter().collect();
-	//Parent array keeps track of each node's parent once the lower cost is established allows to trace back the best possible path
-	let mut parent = vec![None; self.nodes.len()];
-	let mut found = false;
-	while !open.is_empty() {
-	    //min_index is a helper function takes the current distance and returns the index of the node that is the easiest to reach next
-	    let u = min_index(&distance, &open);
-	    //This node is then removed from the open list
-	    let u = open.remove(u);
-	    //Stop if dest is reached
-	    if u == dest {
-		found = true;
-		break; 
-	    }
-	    let dist = distance[u].clone();
-	    //for each edge of this node, the new distance is computed and if lower, inserted into a distance list
-	    for e in &self.adjacency_list[u] {
-		let new_distance = match dist {
-		    TentativeWeight::Number(n) => TentativeWeight::Number(n + e.weight),
-		    _ => TentativeWeight::Infinite,
-		};
-		let old_distance = distance[e.node].clone();
-
-		if new_distance < old_distance {
-		    distance[e.node] = new_distance;
-		    parent[e.node] = Some(u);
-		}
-	    }
-	}
-	//Distance array and a parent array to be prepared for returning to the caller
-	if found {
-	    //Trace back the path from the destination to the orgin node in the parent array, leads to the reverse optimal path between the two nodes
-	    let mut path = vec![];
-	    let mut p = parent[dest].unwrap();
-	    path.push(self.nodes[dest].clone());
-	    while p != src {
-		path.push(self.nodes[p].clone());
-		p = parent[p].unwrap();
-	    }
-	    path.push(self.nodes[src].clone());
-	    
-	    path.reverse();
-	    let cost = match distance[dest] {
-		TentativeWeight::Number(n) => n,
-		_ => 0,
-	    };
-	    Some((cost, path))
-	} else {
-	    None
-	}
+        HashSet::new()
+    }
+}
+
+//Single source Dijkstra over indices using a binary heap. Returns the distance
+//to every node (None if unreachable) and the parent of every node on its best path
+fn dijkstra<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    src: usize,
+) -> (Vec<Option<u32>>, Vec<Option<usize>>) {
+    let mut distance = vec![None; graph.node_count()];
+    let mut parent = vec![None; graph.node_count()];
+    //Reverse turns the max-heap into a min-heap
+    let mut heap = BinaryHeap::new();
+    distance[src] = Some(0);
+    heap.push(Reverse((0u32, src)));
+    while let Some(Reverse((d, u))) = heap.pop() {
+        //Stale heap entry, a cheaper way to u was already settled
+        if distance[u].is_some_and(|best| d > best) {
+            continue;
+        }
+        for (node, weight) in graph.neighbors(u) {
+            let Some(new_distance) = d.checked_add(weight) else {
+                continue;
+            };
+            if distance[node].is_none_or(|old| new_distance < old) {
+                distance[node] = Some(new_distance);
+                parent[node] = Some(u);
+                heap.push(Reverse((new_distance, node)));
+            }
+        }
+    }
+    (distance, parent)
+}
+
+//Spanning trees ignore edge direction, so every edge is listed from both of its ends
+fn undirected_adjacency<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+) -> Vec<Vec<(usize, u32)>> {
+    let mut adjacency: Vec<Vec<(usize, u32)>> = (0..graph.node_count())
+        .map(|i| graph.neighbors(i).collect())
+        .collect();
+    if graph.kind() == GraphKind::Undirected {
+        return adjacency;
+    }
+    for from in 0..graph.node_count() {
+        for (to, weight) in graph.neighbors(from) {
+            adjacency[to].push((from, weight));
+        }
+    }
+    adjacency
+}
+
+//Tarjan's algorithm over indices. The DFS keeps its own call stack of (node, next edge)
+//pairs instead of recursing, so long chains can't overflow the real stack.
+//Components come out sinks first, they are reversed so the result is in topological order
+fn scc_indices<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    let n = graph.node_count();
+    let mut index: Vec<Option<usize>> = vec![None; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut counter = 0;
+    for root in 0..n {
+        if index[root].is_some() {
+            continue;
+        }
+        index[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        let mut call = vec![(root, graph.neighbors(root))];
+        while let Some((v, edges)) = call.last_mut() {
+            let v = *v;
+            if let Some((w, _)) = edges.next() {
+                match index[w] {
+                    None => {
+                        index[w] = Some(counter);
+                        low[w] = counter;
+                        counter += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        call.push((w, graph.neighbors(w)));
+                    }
+                    Some(iw) if on_stack[w] => low[v] = low[v].min(iw),
+                    _ => {}
+                }
+            } else {
+                //All edges of v are done, "return" to the caller
+                call.pop();
+                if let Some((u, _)) = call.last() {
+                    low[*u] = low[*u].min(low[v]);
+                }
+                if Some(low[v]) == index[v] {
+                    let mut component = vec![];
+                    while let Some(w) = stack.pop() {
+                        on_stack[w] = false;
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+    }
+    components.reverse();
+    components
+}
+
+//Result of an all-pairs shortest path run. Rows are sources, columns are destinations,
+//both in the order of the graph's nodes
+pub struct DistanceMatrix<K> {
+    nodes: Vec<K>,
+    index: HashMap<K, usize>,
+    distance: Vec<Vec<Option<u32>>>,
+    parent: Vec<Vec<Option<usize>>>,
+}
+
+impl<K: Clone + Eq + Hash> DistanceMatrix<K> {
+    fn new(
+        nodes: Vec<K>,
+        distance: Vec<Vec<Option<u32>>>,
+        parent: Vec<Vec<Option<usize>>>,
+    ) -> Self {
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.clone(), i))
+            .collect();
+        DistanceMatrix {
+            nodes,
+            index,
+            distance,
+            parent,
+        }
+    }
+
+    pub fn nodes(&self) -> &[K] {
+        &self.nodes
+    }
+
+    //None if either node is unknown or there is no path between them
+    pub fn distance(&self, from: &K, to: &K) -> Option<u32> {
+        let (i, j) = (*self.index.get(from)?, *self.index.get(to)?);
+        self.distance[i][j]
+    }
+
+    //Walks the parent row of the source backwards from the destination,
+    //same as the trace back in shortest_path
+    pub fn path(&self, from: &K, to: &K) -> Option<Vec<K>> {
+        let (i, j) = (*self.index.get(from)?, *self.index.get(to)?);
+        self.distance[i][j]?;
+        let mut path = vec![self.nodes[j].clone()];
+        let mut p = j;
+        while p != i {
+            p = self.parent[i][p]?;
+            path.push(self.nodes[p].clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl<K: Clone + Eq + Hash + Display> DistanceMatrix<K> {
+    //Header row and first column hold the node keys, unreachable pairs are left empty
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for n in &self.nodes {
+            csv.push(',');
+            csv.push_str(&csv_field(&n.to_string()));
+        }
+        csv.push('\n');
+        for (n, row) in self.nodes.iter().zip(&self.distance) {
+            csv.push_str(&csv_field(&n.to_string()));
+            for d in row {
+                csv.push(',');
+                if let Some(d) = d {
+                    csv.push_str(&d.to_string());
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+//Quotes a CSV field if it would otherwise break the row apart
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
     } else {
-	None
+        field.to_string()
     }
 }
 
+pub struct MaxFlow<K> {
+    //u64, parallel paths can add up to more than any single capacity
+    pub value: u64,
+    //Flow through every edge of the graph as (from, to, flow), in adjacency list order
+    pub flows: Vec<(K, K, u32)>,
+    //Min cut partition, the capacity of the edges going from source_side to sink_side is value
+    pub source_side: HashSet<K>,
+    pub sink_side: HashSet<K>,
+}
+
+//Residual network for Dinic over indices. Edges are stored in pairs, edge id ^ 1 is the
+//reverse of edge id, so pushing flow along one simply moves capacity to the other
+struct FlowNetwork {
+    adjacency: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    capacity: Vec<u32>,
+}
+
+impl FlowNetwork {
+    fn new(n: usize) -> Self {
+        FlowNetwork {
+            adjacency: vec![vec![]; n],
+            to: vec![],
+            capacity: vec![],
+        }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, capacity: u32) -> usize {
+        let id = self.to.len();
+        self.adjacency[from].push(id);
+        self.to.push(to);
+        self.capacity.push(capacity);
+        self.adjacency[to].push(id + 1);
+        self.to.push(from);
+        self.capacity.push(0);
+        id
+    }
+
+    //The reverse edge starts out empty, so its capacity is exactly what went through id
+    fn flow(&self, id: usize) -> u32 {
+        self.capacity[id ^ 1]
+    }
+
+    //BFS over edges with capacity left. Without a level the node is unreachable
+    fn levels(&self, s: usize) -> Vec<Option<usize>> {
+        let mut level = vec![None; self.adjacency.len()];
+        let mut queue = VecDeque::new();
+        level[s] = Some(0);
+        queue.push_back(s);
+        while let Some(u) = queue.pop_front() {
+            for &id in &self.adjacency[u] {
+                let v = self.to[id];
+                if self.capacity[id] > 0 && level[v].is_none() {
+                    level[v] = level[u].map(|l| l + 1);
+                    queue.push_back(v);
+                }
+            }
+        }
+        level
+    }
+
+    fn reachable(&self, s: usize) -> Vec<bool> {
+        self.levels(s).into_iter().map(|l| l.is_some()).collect()
+    }
+
+    fn max_flow(&mut self, s: usize, t: usize) -> u64 {
+        let mut total = 0;
+        loop {
+            let level = self.levels(s);
+            if level[t].is_none() {
+                return total;
+            }
+            total += self.blocking_flow(s, t, &level);
+        }
+    }
+
+    //Repeatedly walks level by level from s to t and saturates the path found.
+    //next[u] remembers the first edge of u that may still lead to t, so dead ends
+    //are never explored twice. The walk keeps its path in a Vec instead of recursing
+    fn blocking_flow(&mut self, s: usize, t: usize, level: &[Option<usize>]) -> u64 {
+        let mut next = vec![0; self.adjacency.len()];
+        let mut total = 0;
+        let mut path: Vec<usize> = vec![];
+        let mut u = s;
+        loop {
+            if u == t {
+                let pushed = path.iter().map(|&id| self.capacity[id]).min().unwrap_or(0);
+                for &id in &path {
+                    self.capacity[id] -= pushed;
+                    self.capacity[id ^ 1] += pushed;
+                }
+                total += pushed as u64;
+                path.clear();
+                u = s;
+                continue;
+            }
+            let advance = self.adjacency[u][next[u]..].iter().position(|&id| {
+                self.capacity[id] > 0 && level[self.to[id]] == level[u].map(|l| l + 1)
+            });
+            match advance {
+                Some(skip) => {
+                    next[u] += skip;
+                    let id = self.adjacency[u][next[u]];
+                    path.push(id);
+                    u = self.to[id];
+                }
+                None => {
+                    //Dead end, step back and skip the edge that led here
+                    next[u] = self.adjacency[u].len();
+                    match path.pop() {
+                        Some(id) => {
+                            u = self.to[id ^ 1];
+                            next[u] += 1;
+                        }
+                        None => return total,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
 mod test {
-    use super::ASimpleGraph;
+    use super::{ASimpleGraph, GraphKind};
+    use std::collections::HashSet;
+
+    fn sample() -> ASimpleGraph<&'static str> {
+        let mut graph = ASimpleGraph::new();
+        graph.set_nodes(vec!["a", "b", "c", "d", "e"]);
+        graph.set_edges("a", vec![(4, "b"), (1, "c")]);
+        graph.set_edges("c", vec![(2, "b"), (5, "d")]);
+        graph.set_edges("b", vec![(1, "d")]);
+        graph
+    }
+
     #[test]
     fn basics() {
-	let mut Graph = ASimpleGraph::new();
+        let graph = sample();
+        assert_eq!(
+            graph.shortest_path("a", "d"),
+            Some((4, vec!["a", "c", "b", "d"]))
+        );
+        assert_eq!(graph.shortest_path("a", "a"), Some((0, vec!["a"])));
+        assert_eq!(graph.shortest_path("a", "e"), None);
+        assert_eq!(graph.shortest_path("d", "a"), None);
+
+        let connected = graph.connected("a", 1).unwrap();
+        assert_eq!(connected.len(), 2);
+        assert!(connected.contains("b") && connected.contains("c"));
+    }
+
+    #[test]
+    fn huge_weights() {
+        //a -> b -> c would cost u32::MAX + 1, which no cost can hold
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("a", "b", u32::MAX);
+        graph.add_edge("b", "c", 1);
+        graph.add_edge("c", "d", 1);
+        assert_eq!(
+            graph.shortest_path("a", "b"),
+            Some((u32::MAX, vec!["a", "b"]))
+        );
+        assert_eq!(graph.shortest_path("a", "c"), None);
+        assert_eq!(
+            graph.shortest_path("b", "d"),
+            Some((2, vec!["b", "c", "d"]))
+        );
+        for matrix in [graph.floyd_warshall(), graph.johnson()] {
+            assert_eq!(matrix.distance(&"a", &"b"), Some(u32::MAX));
+            assert_eq!(matrix.distance(&"a", &"c"), None);
+            assert_eq!(matrix.distance(&"b", &"d"), Some(2));
+        }
+        assert_eq!(graph.bidirectional_shortest_path("a", "d"), None);
+        assert_eq!(graph.k_shortest_paths("a", "c", 2), vec![]);
+        assert_eq!(graph.k_shortest_paths("b", "d", 2).len(), 1);
+        let c = graph.betweenness_centrality();
+        assert_eq!((c["b"], c["c"]), (0.0, 1.0));
+    }
+
+    #[test]
+    fn all_pairs() {
+        let graph = sample();
+        for matrix in [graph.floyd_warshall(), graph.johnson()] {
+            assert_eq!(matrix.distance(&"a", &"d"), Some(4));
+            assert_eq!(matrix.distance(&"c", &"d"), Some(3));
+            assert_eq!(matrix.distance(&"b", &"b"), Some(0));
+            assert_eq!(matrix.distance(&"d", &"a"), None);
+            assert_eq!(matrix.distance(&"a", &"z"), None);
+            assert_eq!(matrix.path(&"a", &"d"), Some(vec!["a", "c", "b", "d"]));
+            assert_eq!(matrix.path(&"e", &"e"), Some(vec!["e"]));
+            assert_eq!(matrix.path(&"a", &"e"), None);
+        }
+    }
+
+    #[test]
+    fn csv() {
+        let mut graph = ASimpleGraph::new();
+        graph.set_nodes(vec!["x", "y,z"]);
+        graph.set_edges("x", vec![(7, "y,z")]);
+        assert_eq!(
+            graph.floyd_warshall().to_csv(),
+            ",x,\"y,z\"\nx,0,7\n\"y,z\",,0\n"
+        );
+    }
+
+    #[test]
+    fn spanning_tree() {
+        let mut graph = sample();
+        graph.set_edges("d", vec![(2, "a")]);
+        for (total, tree) in [graph.kruskal(), graph.prim()] {
+            //a-c and b-d for 1 each, then either c-b or d-a for 2
+            assert_eq!(total, 4);
+            assert_eq!(tree.len(), 3);
+            assert_eq!(tree.iter().map(|e| e.2).sum::<u32>(), total);
+            //"e" has no edges, so it is a tree of its own in the forest
+            assert!(tree.iter().all(|e| e.0 != "e" && e.1 != "e"));
+        }
+    }
+
+    //0 -> 1 -> ... -> n-1
+    fn chain(n: usize) -> ASimpleGraph<usize> {
+        let mut graph = ASimpleGraph::new();
+        graph.set_nodes((0..n).collect());
+        for i in 1..n {
+            graph.add_edge(i - 1, i, 1);
+        }
+        graph
+    }
+
+    #[test]
+    fn strongly_connected() {
+        let mut graph = sample();
+        //a -> c -> d -> a closes a cycle through b as well
+        graph.set_edges("d", vec![(2, "a")]);
+        graph.set_edges("e", vec![(3, "a"), (9, "b")]);
+        let components = graph.strongly_connected_components();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0], ["e"].iter().cloned().collect());
+        assert_eq!(
+            components[1],
+            ["a", "b", "c", "d"].iter().cloned().collect()
+        );
+
+        let (dag, components) = graph.condensation();
+        assert_eq!(components.len(), 2);
+        assert_eq!(dag.shortest_path(0, 1), Some((3, vec![0, 1])));
+        assert_eq!(dag.shortest_path(1, 0), None);
+    }
+
+    #[test]
+    fn strongly_connected_deep() {
+        let n = 200_000;
+        let mut graph = chain(n);
+        let components = graph.strongly_connected_components();
+        assert_eq!(components.len(), n);
+        //Topological order, the head of the chain comes first
+        assert!(components[0].contains(&0));
+
+        //Closing the chain turns it into a single component
+        graph.add_edge(n - 1, 0, 1);
+        assert_eq!(graph.strongly_connected_components().len(), 1);
+    }
+
+    #[test]
+    fn max_flow() {
+        let mut graph = ASimpleGraph::new();
+        graph.set_nodes(vec!["s", "a", "b", "c", "d", "t"]);
+        graph.set_edges("s", vec![(10, "a"), (10, "c")]);
+        graph.set_edges("a", vec![(4, "b"), (2, "c"), (8, "d")]);
+        graph.set_edges("c", vec![(9, "d")]);
+        graph.set_edges("b", vec![(10, "t")]);
+        graph.set_edges("d", vec![(6, "b"), (10, "t")]);
+        let flow = graph.max_flow("s", "t").unwrap();
+        assert_eq!(flow.value, 19);
+
+        //Flow is conserved at every inner node and never exceeds the capacity
+        for node in ["a", "b", "c", "d"] {
+            let inflow: u32 = flow.flows.iter().filter(|f| f.1 == node).map(|f| f.2).sum();
+            let outflow: u32 = flow.flows.iter().filter(|f| f.0 == node).map(|f| f.2).sum();
+            assert_eq!(inflow, outflow);
+        }
+        let out_of_source: u32 = flow.flows.iter().filter(|f| f.0 == "s").map(|f| f.2).sum();
+        assert_eq!(out_of_source, 19);
+
+        //The cut edges add up to the flow value
+        assert!(flow.source_side.contains("s") && flow.sink_side.contains("t"));
+        let mut cut = 0;
+        for (from, edges) in graph.adjacency_list.iter().enumerate() {
+            for e in edges {
+                if flow.source_side.contains(graph.nodes[from])
+                    && flow.sink_side.contains(graph.nodes[e.node])
+                {
+                    cut += e.weight;
+                }
+            }
+        }
+        assert_eq!(cut, 19);
+
+        assert!(graph.max_flow("s", "s").is_none());
+        assert!(graph.max_flow("s", "z").is_none());
+        assert_eq!(graph.max_flow("t", "s").unwrap().value, 0);
+    }
+
+    #[test]
+    fn saturated_flow() {
+        //Two full paths side by side carry twice what fits into a u32
+        let mut graph = ASimpleGraph::new();
+        for middle in ["a", "b"] {
+            graph.add_edge("s", middle, u32::MAX);
+            graph.add_edge(middle, "t", u32::MAX);
+        }
+        let flow = graph.max_flow("s", "t").unwrap();
+        assert_eq!(flow.value, 2 * u32::MAX as u64);
+        assert!(flow.flows.iter().all(|&(_, _, f)| f == u32::MAX));
+        assert_eq!(flow.sink_side.len(), 3);
+    }
+
+    #[test]
+    fn bipartite_matching() {
+        let mut graph = ASimpleGraph::new();
+        graph.set_nodes(vec!["x1", "x2", "x3", "y1", "y2", "y3"]);
+        graph.set_edges("x1", vec![(1, "y1"), (1, "y2")]);
+        graph.set_edges("x2", vec![(1, "y1")]);
+        //Direction doesn't matter for matching
+        graph.set_edges("y3", vec![(1, "x1")]);
+        let left = ["x1", "x2", "x3"].iter().cloned().collect();
+        let matching = graph.bipartite_matching(&left);
+        assert_eq!(matching.len(), 2);
+        assert!(matching.contains(&("x2", "y1")));
+        let (lefts, rights): (HashSet<_>, HashSet<_>) = matching.into_iter().unzip();
+        assert_eq!(lefts.len(), 2);
+        assert_eq!(rights.len(), 2);
+    }
+
+    //Every key maps to its own slot and no edge points past the end
+    fn assert_consistent<K: Clone + Eq + std::hash::Hash + std::fmt::Debug>(
+        graph: &ASimpleGraph<K>,
+    ) {
+        assert_eq!(graph.index.len(), graph.nodes.len());
+        assert_eq!(graph.adjacency_list.len(), graph.nodes.len());
+        for (i, n) in graph.nodes.iter().enumerate() {
+            assert_eq!(graph.index[n], i);
+        }
+        for edges in &graph.adjacency_list {
+            assert!(edges.iter().all(|e| e.node < graph.nodes.len()));
+        }
+    }
+
+    #[test]
+    fn add_and_remove() {
+        let mut graph = sample();
+        graph.add_edge("d", "f", 2);
+        graph.add_edge("f", "e", 3);
+        assert_consistent(&graph);
+        assert_eq!(
+            graph.shortest_path("a", "e"),
+            Some((9, vec!["a", "c", "b", "d", "f", "e"]))
+        );
+
+        assert_eq!(graph.remove_edge("b", "d"), Some(1));
+        assert_eq!(graph.remove_edge("b", "d"), None);
+        assert_eq!(graph.remove_edge("b", "z"), None);
+        assert_eq!(
+            graph.shortest_path("a", "d"),
+            Some((6, vec!["a", "c", "d"]))
+        );
+
+        //"c" sits in the middle, "f" is the last node and gets moved into its slot
+        assert!(graph.remove_node("c"));
+        assert!(!graph.remove_node("c"));
+        assert_consistent(&graph);
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.shortest_path("a", "c"), None);
+        assert_eq!(graph.shortest_path("a", "d"), None);
+        assert_eq!(
+            graph.shortest_path("d", "e"),
+            Some((5, vec!["d", "f", "e"]))
+        );
+
+        //Removing the last node needs no swap
+        assert!(graph.remove_node("f"));
+        assert_consistent(&graph);
+        assert_eq!(graph.shortest_path("d", "e"), None);
+
+        for n in ["a", "b", "d", "e"] {
+            assert!(graph.remove_node(n));
+            assert_consistent(&graph);
+        }
+        assert!(graph.nodes.is_empty());
+    }
+
+    #[test]
+    fn undirected() {
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        graph.set_nodes(vec!["a", "b", "c"]);
+        graph.set_edges("a", vec![(4, "b"), (1, "c")]);
+        assert!(graph.add_edge("c", "b", 1));
+        assert_eq!(
+            graph.shortest_path("b", "a"),
+            Some((2, vec!["b", "c", "a"]))
+        );
+
+        //Setting the edges of a again drops the mirrors of the old ones
+        graph.set_edges("a", vec![(7, "b")]);
+        assert_eq!(
+            graph.edges("b").unwrap(),
+            vec![(1, "c", None), (7, "a", None)]
+        );
+        assert_eq!(graph.edges("c").unwrap(), vec![(1, "b", None)]);
+
+        //Adding an edge again updates both ends
+        assert!(!graph.add_edge("b", "a", 3));
+        assert_eq!(graph.shortest_path("a", "b"), Some((3, vec!["a", "b"])));
+
+        assert_eq!(graph.remove_edge("b", "a"), Some(3));
+        assert_eq!(graph.edges("a").unwrap(), vec![]);
+
+        //Self loops are only stored once
+        assert!(graph.add_edge("c", "c", 5));
+        assert_eq!(graph.edges("c").unwrap().len(), 2);
+        assert_eq!(graph.remove_edge("c", "c"), Some(5));
+        assert_eq!(graph.edges("c").unwrap(), vec![(1, "b", None)]);
+    }
+
+    #[test]
+    fn multigraph() {
+        let mut graph = ASimpleGraph::multigraph(GraphKind::Directed);
+        assert!(graph.add_labeled_edge("a", "b", 5, "road"));
+        assert!(graph.add_labeled_edge("a", "b", 2, "rail"));
+        assert!(!graph.add_labeled_edge("a", "b", 4, "road"));
+        assert_eq!(
+            graph.edges("a").unwrap(),
+            vec![(4, "b", Some("road")), (2, "b", Some("rail"))]
+        );
+        assert_eq!(graph.shortest_path("a", "b"), Some((2, vec!["a", "b"])));
+        assert_eq!(graph.remove_labeled_edge("a", "b", "rail"), Some(2));
+        assert_eq!(graph.remove_labeled_edge("a", "b", "rail"), None);
+        assert_eq!(graph.shortest_path("a", "b"), Some((4, vec!["a", "b"])));
+
+        //A simple graph keeps one edge per pair whatever the label
+        let mut graph = ASimpleGraph::new();
+        assert!(graph.add_labeled_edge("a", "b", 5, "road"));
+        assert!(!graph.add_labeled_edge("a", "b", 2, "rail"));
+        assert_eq!(graph.edges("a").unwrap(), vec![(2, "b", Some("rail"))]);
+
+        let mut graph = ASimpleGraph::multigraph(GraphKind::Undirected);
+        graph.add_labeled_edge("a", "b", 5, "road");
+        graph.add_labeled_edge("b", "a", 2, "rail");
+        assert_eq!(graph.edges("a").unwrap().len(), 2);
+        assert_eq!(graph.remove_labeled_edge("a", "b", "road"), Some(5));
+        assert_eq!(graph.edges("b").unwrap(), vec![(2, "a", Some("rail"))]);
+    }
+
+    //Callers don't have to import GraphView, the inherent methods answer the same way
+    //the trait does
+    mod without_trait {
+        use std::collections::HashSet;
+
+        #[test]
+        fn inherent() {
+            let graph = super::sample();
+            assert_eq!(
+                graph.shortest_path("a", "d"),
+                Some((4, vec!["a", "c", "b", "d"]))
+            );
+            assert_eq!(graph.connected("a", 1).map(|c| c.len()), Some(2));
+            assert_eq!(graph.floyd_warshall().distance(&"a", &"d"), Some(4));
+            assert_eq!(graph.johnson().distance(&"c", &"d"), Some(3));
+            assert_eq!(graph.kruskal().0, graph.prim().0);
+            assert_eq!(graph.strongly_connected_components().len(), 5);
+            assert_eq!(graph.condensation().1.len(), 5);
+            assert_eq!(graph.max_flow("a", "d").map(|f| f.value), Some(2));
+            let left: HashSet<&str> = ["a", "c"].iter().cloned().collect();
+            assert_eq!(graph.bipartite_matching(&left).len(), 2);
+            //The queries that came with GraphView too
+            assert_eq!(graph.k_shortest_paths("a", "d", 2)[1].0, 5);
+            assert_eq!(graph.bidirectional_shortest_path("a", "d").unwrap().0, 4);
+            assert!(graph.betweenness_centrality()["c"] > 0.0);
+            assert_eq!(graph.pagerank(0.85, 1e-10).len(), 5);
+            assert!(graph.reachability().reaches(&"a", &"d"));
+            assert!(graph.eulerian_circuit().is_err());
+        }
     }
 }
@@ -0,0 +1,217 @@
+//Articulation points, bridges and biconnected components all come out of the same
+//DFS (Hopcroft and Tarjan). disc is the order nodes are discovered in, low the
+//earliest discovered node reachable from the subtree of a node with one back edge.
+//  u is an articulation point if some child v has low[v] >= disc[u] (the root needs
+//  two children instead)
+//  the tree edge u-v is a bridge if low[v] > disc[u]
+//The DFS keeps its own stack of (node, edge it came in on, next neighbor) instead
+//of recursing, the same way scc_indices does
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use super::{GraphKind, GraphView};
+
+pub(super) struct Biconnectivity {
+    pub(super) articulation: Vec<bool>,
+    pub(super) bridges: Vec<(usize, usize)>,
+    pub(super) components: Vec<Vec<usize>>,
+}
+
+//Every undirected edge gets an id, so the DFS can tell the edge it came in on apart
+//from a parallel edge back to its parent. An undirected graph stores both ends of
+//each edge, only the one from the lower index is kept. A directed graph counts a
+//pair of nodes as linked once, whichever way its edges point. Self loops never matter
+fn undirected_edges<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+) -> Vec<(usize, usize)> {
+    let mut edges = vec![];
+    let mut seen = HashSet::new();
+    for from in 0..graph.node_count() {
+        for (to, _) in graph.neighbors(from) {
+            if from == to {
+                continue;
+            }
+            match graph.kind() {
+                GraphKind::Undirected if from < to => edges.push((from, to)),
+                GraphKind::Directed if seen.insert((from.min(to), from.max(to))) => {
+                    edges.push((from, to))
+                }
+                _ => {}
+            }
+        }
+    }
+    edges
+}
+
+pub(super) fn search<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(graph: &G) -> Biconnectivity {
+    let n = graph.node_count();
+    let edges = undirected_edges(graph);
+    let mut adjacency: Vec<Vec<(usize, usize)>> = vec![vec![]; n];
+    for (id, &(u, v)) in edges.iter().enumerate() {
+        adjacency[u].push((v, id));
+        adjacency[v].push((u, id));
+    }
+
+    let mut disc: Vec<Option<usize>> = vec![None; n];
+    let mut low = vec![0; n];
+    let mut counter = 0;
+    let mut found = Biconnectivity {
+        articulation: vec![false; n],
+        bridges: vec![],
+        components: vec![],
+    };
+    //Edges of the component being built, it's closed off at an articulation point
+    let mut edge_stack: Vec<usize> = vec![];
+    for root in 0..n {
+        if disc[root].is_some() {
+            continue;
+        }
+        disc[root] = Some(counter);
+        low[root] = counter;
+        counter += 1;
+        let mut children = 0;
+        let mut call: Vec<(usize, Option<usize>, usize)> = vec![(root, None, 0)];
+        while let Some((v, parent_edge, i)) = call.last_mut() {
+            let (v, parent_edge) = (*v, *parent_edge);
+            if let Some(&(w, id)) = adjacency[v].get(*i) {
+                *i += 1;
+                if Some(id) == parent_edge {
+                    continue;
+                }
+                match disc[w] {
+                    None => {
+                        disc[w] = Some(counter);
+                        low[w] = counter;
+                        counter += 1;
+                        edge_stack.push(id);
+                        if v == root {
+                            children += 1;
+                        }
+                        call.push((w, Some(id), 0));
+                    }
+                    //Back edge to an ancestor. Edges to descendants were seen from
+                    //the other end already
+                    Some(dw) if Some(dw) < disc[v] => {
+                        low[v] = low[v].min(dw);
+                        edge_stack.push(id);
+                    }
+                    _ => {}
+                }
+            } else {
+                //All edges of v are done, "return" to the parent u
+                call.pop();
+                let (u, id) = match (call.last(), parent_edge) {
+                    (Some(&(u, _, _)), Some(id)) => (u, id),
+                    _ => continue,
+                };
+                low[u] = low[u].min(low[v]);
+                let du = disc[u].unwrap();
+                if low[v] > du {
+                    found.bridges.push(edges[id]);
+                }
+                if low[v] >= du {
+                    if u != root {
+                        found.articulation[u] = true;
+                    }
+                    let mut component = HashSet::new();
+                    while let Some(e) = edge_stack.pop() {
+                        component.insert(edges[e].0);
+                        component.insert(edges[e].1);
+                        if e == id {
+                            break;
+                        }
+                    }
+                    found.components.push(component.into_iter().collect());
+                }
+            }
+        }
+        if children > 1 {
+            found.articulation[root] = true;
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{ASimpleGraph, GraphKind};
+    use std::collections::HashSet;
+
+    fn set(keys: &[&'static str]) -> HashSet<&'static str> {
+        keys.iter().cloned().collect()
+    }
+
+    //Two triangles a-b-c and c-d-e sharing c, plus a tail e-f
+    fn bowtie() -> ASimpleGraph<&'static str> {
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        for (u, v) in [
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "c"),
+            ("e", "f"),
+        ] {
+            graph.add_edge(u, v, 1);
+        }
+        graph.add_edge("g", "g", 1);
+        graph
+    }
+
+    #[test]
+    fn basics() {
+        let graph = bowtie();
+        assert_eq!(graph.articulation_points(), set(&["c", "e"]));
+        assert_eq!(graph.bridges(), vec![("e", "f")]);
+        let mut components = graph.biconnected_components();
+        components.sort_by_key(|c| (c.len(), c.iter().min().cloned()));
+        assert_eq!(
+            components,
+            vec![
+                set(&["e", "f"]),
+                set(&["a", "b", "c"]),
+                set(&["c", "d", "e"])
+            ]
+        );
+    }
+
+    #[test]
+    fn parallel_and_directed() {
+        //A second edge between e and f means cutting one of them isn't enough
+        let mut graph = ASimpleGraph::multigraph(GraphKind::Undirected);
+        graph.add_labeled_edge("e", "f", 1, "x");
+        graph.add_labeled_edge("e", "f", 1, "y");
+        graph.add_labeled_edge("f", "g", 1, "x");
+        assert_eq!(graph.bridges(), vec![("f", "g")]);
+        assert_eq!(graph.articulation_points(), set(&["f"]));
+
+        //Direction doesn't matter, and a -> b plus b -> a is still a single link
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("a", "b", 1);
+        graph.add_edge("b", "a", 1);
+        graph.add_edge("c", "b", 1);
+        assert_eq!(graph.bridges().len(), 2);
+        assert_eq!(graph.articulation_points(), set(&["b"]));
+    }
+
+    #[test]
+    fn deep() {
+        //A long path: every inner node is an articulation point, every edge a bridge
+        let n = 200_000;
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        graph.set_nodes((0..n).collect());
+        for i in 1..n {
+            graph.add_edge(i - 1, i, 1);
+        }
+        assert_eq!(graph.articulation_points().len(), n - 2);
+        assert_eq!(graph.bridges().len(), n - 1);
+        assert_eq!(graph.biconnected_components().len(), n - 1);
+
+        //Closing it into a ring leaves one big component
+        graph.add_edge(n - 1, 0, 1);
+        assert!(graph.articulation_points().is_empty());
+        assert!(graph.bridges().is_empty());
+        assert_eq!(graph.biconnected_components().len(), 1);
+    }
+}
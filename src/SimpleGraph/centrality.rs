@@ -0,0 +1,379 @@
+//Node rankings behind the centrality methods of GraphView.
+//Everything works on indices and only turns into keys at the very end
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{dijkstra, scc_indices, GraphKind, GraphView};
+
+//PageRank gives up after this many rounds even if it hasn't settled yet
+const MAX_ITERATIONS: usize = 1000;
+
+fn by_key<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    values: Vec<f64>,
+) -> HashMap<K, f64> {
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (graph.node(i).clone(), v))
+        .collect()
+}
+
+pub(super) fn degree<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    incoming: bool,
+) -> HashMap<K, f64> {
+    let n = graph.node_count();
+    let mut degree = vec![0.0; n];
+    for from in 0..n {
+        for (to, _) in graph.neighbors(from) {
+            if incoming {
+                degree[to] += 1.0;
+            } else {
+                degree[from] += 1.0;
+            }
+        }
+    }
+    if n > 1 {
+        for d in &mut degree {
+            *d /= (n - 1) as f64;
+        }
+    }
+    by_key(graph, degree)
+}
+
+//Bare adjacency lists over 0..n, for the graph betweenness merges the zero weight
+//cycles out of
+struct Adjacency {
+    nodes: Vec<usize>,
+    edges: Vec<Vec<(usize, u32)>>,
+}
+
+impl Adjacency {
+    fn new(edges: Vec<Vec<(usize, u32)>>) -> Self {
+        Adjacency {
+            nodes: (0..edges.len()).collect(),
+            edges,
+        }
+    }
+}
+
+impl GraphView<usize> for Adjacency {
+    fn node_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn node(&self, i: usize) -> &usize {
+        &self.nodes[i]
+    }
+
+    fn node_index(&self, node: &usize) -> Option<usize> {
+        (*node < self.edges.len()).then_some(*node)
+    }
+
+    fn neighbors(&self, i: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        self.edges[i].iter().cloned()
+    }
+
+    fn kind(&self) -> GraphKind {
+        GraphKind::Directed
+    }
+}
+
+//Nodes on a cycle of zero weight edges are all the same distance from everywhere, and
+//a path could go round and round between them. Each such group (a strongly connected
+//component of the zero weight edges) is merged into one node first, so a path through
+//the group counts once and every member gets the credit for it. That also covers both
+//ends of an undirected zero weight edge
+pub(super) fn betweenness<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+) -> HashMap<K, f64> {
+    let n = graph.node_count();
+    let zero = Adjacency::new(
+        (0..n)
+            .map(|u| graph.neighbors(u).filter(|&(_, w)| w == 0).collect())
+            .collect(),
+    );
+    let groups = scc_indices(&zero);
+    let mut group_of = vec![0; n];
+    for (g, members) in groups.iter().enumerate() {
+        for &u in members {
+            group_of[u] = g;
+        }
+    }
+    let mut edges = vec![vec![]; groups.len()];
+    for u in 0..n {
+        for (v, w) in graph.neighbors(u) {
+            if group_of[u] != group_of[v] {
+                edges[group_of[u]].push((group_of[v], w));
+            }
+        }
+    }
+    let size: Vec<f64> = groups.iter().map(|members| members.len() as f64).collect();
+    let credit = brandes(&Adjacency::new(edges), &size);
+    let mut centrality: Vec<f64> = group_of.iter().map(|&g| credit[g]).collect();
+    //Undirected graphs walk every path in both directions
+    if graph.kind() == GraphKind::Undirected {
+        for c in &mut centrality {
+            *c /= 2.0;
+        }
+    }
+    by_key(graph, centrality)
+}
+
+//Brandes over a graph without zero weight cycles where node v stands for size[v]
+//nodes, so it counts as that many sources and that many targets
+fn brandes(graph: &Adjacency, size: &[f64]) -> Vec<f64> {
+    let n = graph.node_count();
+    let mut centrality = vec![0.0; n];
+    for s in 0..n {
+        //Edges that reach w at exactly its distance are the ones shortest paths use
+        let (distance, _) = dijkstra(graph, s);
+        let tight = |v: usize, w: usize, weight: u32| match (distance[v], distance[w]) {
+            (Some(dv), Some(dw)) => dv.checked_add(weight) == Some(dw),
+            _ => false,
+        };
+        //Paths have to be counted up to a node before it passes them on, and Dijkstra's
+        //settling order doesn't guarantee that once an edge weighs 0. Reverse post order
+        //of a DFS over the tight edges does, they can't form a cycle any more
+        let mut order = vec![];
+        let mut visited = vec![false; n];
+        visited[s] = true;
+        let mut call = vec![(s, graph.neighbors(s))];
+        while let Some((v, edges)) = call.last_mut() {
+            let v = *v;
+            match edges.find(|&(w, weight)| !visited[w] && tight(v, w, weight)) {
+                Some((w, _)) => {
+                    visited[w] = true;
+                    call.push((w, graph.neighbors(w)));
+                }
+                None => {
+                    call.pop();
+                    order.push(v);
+                }
+            }
+        }
+        order.reverse();
+        //Number of shortest paths (sigma) to every node and the predecessors on them
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; n];
+        let mut sigma = vec![0.0; n];
+        sigma[s] = 1.0;
+        for &v in &order {
+            for (w, weight) in graph.neighbors(v) {
+                if tight(v, w, weight) {
+                    sigma[w] += sigma[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+        //Dependencies flow back from the far end towards s
+        let mut delta = vec![0.0; n];
+        while let Some(w) = order.pop() {
+            for &v in &predecessors[w] {
+                delta[v] += sigma[v] / sigma[w] * (size[w] + delta[w]);
+            }
+            if w != s {
+                centrality[w] += size[s] * delta[w];
+            }
+        }
+    }
+    centrality
+}
+
+pub(super) fn closeness<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+) -> HashMap<K, f64> {
+    let n = graph.node_count();
+    let closeness = (0..n)
+        .map(|u| {
+            let (distance, _) = dijkstra(graph, u);
+            let reached: Vec<u32> = distance.into_iter().flatten().collect();
+            let total: u64 = reached.iter().map(|&d| d as u64).sum();
+            let others = (reached.len() - 1) as f64;
+            if total == 0 || n < 2 {
+                0.0
+            } else {
+                (others / total as f64) * (others / (n - 1) as f64)
+            }
+        })
+        .collect();
+    by_key(graph, closeness)
+}
+
+pub(super) fn pagerank<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    damping: f64,
+    tolerance: f64,
+) -> HashMap<K, f64> {
+    let n = graph.node_count();
+    if n == 0 {
+        return HashMap::new();
+    }
+    let out_weight: Vec<f64> = (0..n)
+        .map(|i| graph.neighbors(i).map(|(_, w)| w as f64).sum())
+        .collect();
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n)
+            .filter(|&i| out_weight[i] == 0.0)
+            .map(|i| rank[i])
+            .sum();
+        let base = (1.0 - damping) / n as f64 + damping * dangling / n as f64;
+        let mut next = vec![base; n];
+        for (from, &total) in out_weight.iter().enumerate() {
+            if total == 0.0 {
+                continue;
+            }
+            for (to, w) in graph.neighbors(from) {
+                next[to] += damping * rank[from] * w as f64 / total;
+            }
+        }
+        let change: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if change < tolerance {
+            break;
+        }
+    }
+    by_key(graph, rank)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{ASimpleGraph, GraphKind};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn degree() {
+        let mut graph = ASimpleGraph::new();
+        graph.set_nodes(vec!["a", "b", "c"]);
+        graph.set_edges("a", vec![(1, "b"), (1, "c")]);
+        graph.set_edges("b", vec![(1, "c")]);
+        let (inward, outward) = (graph.in_degree_centrality(), graph.out_degree_centrality());
+        assert!(close(inward["a"], 0.0) && close(inward["c"], 1.0));
+        assert!(close(outward["a"], 1.0) && close(outward["b"], 0.5));
+    }
+
+    #[test]
+    fn betweenness() {
+        //Path a - b - c - d: b sits on a-c, a-d, c sits on a-d, b-d
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        graph.set_nodes(vec!["a", "b", "c", "d"]);
+        graph.add_edge("a", "b", 1);
+        graph.add_edge("b", "c", 1);
+        graph.add_edge("c", "d", 1);
+        let c = graph.betweenness_centrality();
+        assert!(close(c["a"], 0.0) && close(c["d"], 0.0));
+        assert!(close(c["b"], 2.0) && close(c["c"], 2.0));
+
+        //Two equally short ways from s to t, each middle node gets half the credit
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("s", "x", 1);
+        graph.add_edge("s", "y", 2);
+        graph.add_edge("x", "t", 2);
+        graph.add_edge("y", "t", 1);
+        let c = graph.betweenness_centrality();
+        assert!(close(c["x"], 0.5) && close(c["y"], 0.5) && close(c["s"], 0.0));
+
+        //A heavier direct edge loses against the detour
+        graph.add_edge("s", "t", 10);
+        let c = graph.betweenness_centrality();
+        assert!(close(c["x"], 0.5));
+    }
+
+    #[test]
+    fn zero_weights() {
+        //x is settled before y, but the free edge y -> x adds a second shortest way to
+        //x, and t has to count it too. s-x-t, s-y-x-t, s-y-x and y-x-t
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("s", "x", 1);
+        graph.add_edge("s", "y", 1);
+        graph.add_edge("y", "x", 0);
+        graph.add_edge("x", "t", 1);
+        let c = graph.betweenness_centrality();
+        assert!(close(c["x"], 2.0) && close(c["y"], 1.0));
+
+        //a and b are free to go back and forth. Together they are one stop on the way
+        //from s to t, and that is the only pair they sit between
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("s", "a", 1);
+        graph.add_edge("a", "b", 0);
+        graph.add_edge("b", "a", 0);
+        graph.add_edge("b", "t", 1);
+        let c = graph.betweenness_centrality();
+        assert!(close(c["a"], 1.0) && close(c["b"], 1.0));
+        assert!(close(c["s"], 0.0) && close(c["t"], 0.0));
+
+        //Nothing tells a and b apart, whichever order their edges come in
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("s", "a", 1);
+        graph.add_edge("s", "b", 1);
+        graph.add_edge("a", "b", 0);
+        graph.add_edge("b", "a", 0);
+        let c = graph.betweenness_centrality();
+        assert!(close(c["a"], c["b"]));
+        for flip in [false, true] {
+            let mut graph = ASimpleGraph::new();
+            let (first, second) = if flip { ("b", "a") } else { ("a", "b") };
+            graph.add_edge("s", first, 1);
+            graph.add_edge("s", second, 1);
+            graph.add_edge(first, second, 0);
+            graph.add_edge(second, first, 0);
+            graph.add_edge("b", "t", 1);
+            let c = graph.betweenness_centrality();
+            assert!(close(c["a"], c["b"]) && close(c["a"], 1.0));
+        }
+
+        //An undirected zero weight edge ties its ends together the same way
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        graph.add_edge("s", "a", 1);
+        graph.add_edge("a", "b", 0);
+        graph.add_edge("b", "t", 1);
+        let c = graph.betweenness_centrality();
+        assert!(close(c["a"], 1.0) && close(c["b"], 1.0));
+    }
+
+    #[test]
+    fn closeness() {
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        graph.set_nodes(vec!["a", "b", "c", "d"]);
+        graph.add_edge("a", "b", 1);
+        graph.add_edge("b", "c", 1);
+        let c = graph.closeness_centrality();
+        //b reaches 2 nodes at total distance 2, but only 2 of the 3 other nodes
+        assert!(close(c["b"], 1.0 * 2.0 / 3.0));
+        assert!(close(c["a"], (2.0 / 3.0) * (2.0 / 3.0)));
+        assert!(close(c["d"], 0.0));
+    }
+
+    #[test]
+    fn pagerank() {
+        //A symmetric cycle shares rank evenly
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 0, 1);
+        let rank = graph.pagerank(0.85, 1e-10);
+        assert!(rank.values().all(|&r| close(r, 1.0 / 3.0)));
+
+        //Everyone links to the hub, the hub is a dangling node
+        let mut graph = ASimpleGraph::new();
+        for i in 1..5 {
+            graph.add_edge(i, 0, 1);
+        }
+        let rank = graph.pagerank(0.85, 1e-10);
+        assert!(close(rank.values().sum(), 1.0));
+        assert!((1..5).all(|i| rank[&0] > rank[&i]));
+
+        //Weights split rank unevenly
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("a", "b", 3);
+        graph.add_edge("a", "c", 1);
+        graph.add_edge("b", "a", 1);
+        graph.add_edge("c", "a", 1);
+        let rank = graph.pagerank(0.85, 1e-10);
+        assert!(rank["b"] > rank["c"]);
+    }
+}
@@ -0,0 +1,236 @@
+//Two colorings that ignore edge direction, since an edge either way means the two
+//ends can't share a color:
+//  two_color, BFS that colors every layer the other color than the one before.
+//    Either it works and the graph is bipartite, or some edge joins two nodes of the
+//    same layer, and together with their BFS paths back to a common ancestor that
+//    edge closes a cycle of odd length
+//  greedy, colors the nodes in some order, each with the lowest color none of its
+//    neighbors has. The order makes all the difference
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use super::{undirected_adjacency, GraphView};
+
+//Node orders for greedy coloring
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColoringOrder {
+    //Highest degree first, so the hard nodes get colored while colors are still free
+    WelshPowell,
+    //Next is always the node whose neighbors already have the most distinct colors
+    //(its saturation), ties go to the higher degree. Exact on bipartite graphs
+    DSatur,
+}
+
+//Distinct neighbors of every node, self loops left out
+fn neighbor_sets<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    undirected_adjacency(graph)
+        .into_iter()
+        .enumerate()
+        .map(|(i, edges)| {
+            let set: HashSet<usize> = edges
+                .into_iter()
+                .map(|(to, _)| to)
+                .filter(|&to| to != i)
+                .collect();
+            set.into_iter().collect()
+        })
+        .collect()
+}
+
+//Ok with the side of every node, Err with the nodes of an odd cycle in order
+pub(super) fn two_color<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+) -> Result<Vec<bool>, Vec<usize>> {
+    let n = graph.node_count();
+    let adjacency = undirected_adjacency(graph);
+    let mut side: Vec<Option<bool>> = vec![None; n];
+    let mut parent = vec![None; n];
+    for root in 0..n {
+        if side[root].is_some() {
+            continue;
+        }
+        side[root] = Some(false);
+        let mut queue = VecDeque::from(vec![root]);
+        while let Some(u) = queue.pop_front() {
+            for &(v, _) in &adjacency[u] {
+                match side[v] {
+                    None => {
+                        side[v] = side[u].map(|s| !s);
+                        parent[v] = Some(u);
+                        queue.push_back(v);
+                    }
+                    Some(s) if Some(s) == side[u] => return Err(odd_cycle(&parent, u, v)),
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(side.into_iter().map(Option::unwrap).collect())
+}
+
+//u and v sit at the same depth of the BFS tree, so walking both up one step at a
+//time meets at their lowest common ancestor. u up to it, then back down to v
+fn odd_cycle(parent: &[Option<usize>], u: usize, v: usize) -> Vec<usize> {
+    let (mut up, mut down) = (vec![u], vec![v]);
+    let (mut a, mut b) = (u, v);
+    while a != b {
+        a = parent[a].unwrap();
+        b = parent[b].unwrap();
+        up.push(a);
+        down.push(b);
+    }
+    //The common ancestor is at the end of both
+    down.pop();
+    up.extend(down.into_iter().rev());
+    up
+}
+
+//Lowest color that isn't taken by any neighbor. A node with d neighbors always
+//finds one in 0..=d
+fn lowest_free(neighbors: &[usize], color: &[Option<usize>]) -> usize {
+    let mut taken = vec![false; neighbors.len() + 1];
+    for &v in neighbors {
+        if let Some(c) = color[v].filter(|&c| c < taken.len()) {
+            taken[c] = true;
+        }
+    }
+    taken.iter().position(|&t| !t).unwrap()
+}
+
+pub(super) fn greedy<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    order: ColoringOrder,
+) -> Vec<usize> {
+    let n = graph.node_count();
+    let neighbors = neighbor_sets(graph);
+    let mut color: Vec<Option<usize>> = vec![None; n];
+    match order {
+        ColoringOrder::WelshPowell => {
+            let mut nodes: Vec<usize> = (0..n).collect();
+            nodes.sort_by_key(|&i| Reverse(neighbors[i].len()));
+            for u in nodes {
+                color[u] = Some(lowest_free(&neighbors[u], &color));
+            }
+        }
+        ColoringOrder::DSatur => {
+            //Colors seen around every node. The heap holds (saturation, degree, node)
+            //and gets a fresh entry whenever a saturation goes up, old ones are skipped
+            let mut seen: Vec<HashSet<usize>> = vec![HashSet::new(); n];
+            let mut heap: BinaryHeap<(usize, usize, Reverse<usize>)> = (0..n)
+                .map(|i| (0, neighbors[i].len(), Reverse(i)))
+                .collect();
+            while let Some((saturation, _, Reverse(u))) = heap.pop() {
+                if color[u].is_some() || saturation < seen[u].len() {
+                    continue;
+                }
+                let c = lowest_free(&neighbors[u], &color);
+                color[u] = Some(c);
+                for &v in &neighbors[u] {
+                    if color[v].is_none() && seen[v].insert(c) {
+                        heap.push((seen[v].len(), neighbors[v].len(), Reverse(v)));
+                    }
+                }
+            }
+        }
+    }
+    color.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::generate::{complete, erdos_renyi, grid};
+    use super::super::{ASimpleGraph, GraphKind, GraphView};
+    use super::ColoringOrder;
+    use crate::rng::Rng;
+    use std::collections::{HashMap, HashSet};
+
+    fn assert_proper(graph: &ASimpleGraph<usize>, color: &HashMap<usize, usize>) {
+        assert_eq!(color.len(), graph.node_count());
+        for from in 0..graph.node_count() {
+            for (to, _) in graph.neighbors(from) {
+                assert!(from == to || color[&from] != color[&to]);
+            }
+        }
+    }
+
+    fn colors(color: &HashMap<usize, usize>) -> usize {
+        color.values().max().map_or(0, |c| c + 1)
+    }
+
+    #[test]
+    fn bipartite() {
+        let graph = grid(4, 5);
+        let (left, right) = graph.is_bipartite().unwrap();
+        assert_eq!((left.len(), right.len()), (10, 10));
+        for from in 0..20 {
+            for (to, _) in graph.neighbors(from) {
+                assert_ne!(left.contains(&from), left.contains(&to));
+            }
+        }
+
+        //Square with a tail, then a diagonal that makes two triangles
+        let mut graph = ASimpleGraph::new();
+        for (from, to) in [("a", "b"), ("b", "c"), ("c", "d"), ("d", "a"), ("d", "e")] {
+            graph.add_edge(from, to, 1);
+        }
+        assert!(graph.is_bipartite().is_ok());
+        graph.add_edge("c", "a", 1);
+        let cycle = graph.is_bipartite().unwrap_err();
+        assert_eq!(cycle.len(), 3);
+        for i in 0..cycle.len() {
+            let (u, v) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+            //Directed graph, the edge can point either way
+            let linked = |a, b| graph.edges(a).unwrap().iter().any(|e| e.1 == b);
+            assert!(linked(u, v) || linked(v, u));
+        }
+
+        let mut graph = ASimpleGraph::new();
+        graph.add_edge("x", "x", 1);
+        assert_eq!(graph.is_bipartite(), Err(vec!["x"]));
+    }
+
+    #[test]
+    fn odd_cycle_deep() {
+        //A ring with an odd number of nodes, the witness is the whole ring
+        let n = 100_001;
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        graph.set_nodes((0..n).collect());
+        for i in 0..n {
+            graph.add_edge(i, (i + 1) % n, 1);
+        }
+        assert_eq!(graph.is_bipartite().unwrap_err().len(), n);
+    }
+
+    #[test]
+    fn greedy() {
+        for order in [ColoringOrder::WelshPowell, ColoringOrder::DSatur] {
+            let graph = complete(7, GraphKind::Undirected);
+            let color = graph.greedy_coloring(order);
+            assert_proper(&graph, &color);
+            assert_eq!(colors(&color), 7);
+
+            for seed in 0..5 {
+                let graph = erdos_renyi(100, 0.1, GraphKind::Directed, &mut Rng::new(seed));
+                let color = graph.greedy_coloring(order);
+                assert_proper(&graph, &color);
+                //Never more than the highest degree plus one. Coloring ignores the
+                //direction, so the degree counts every other end of an edge once
+                let mut adjacent = vec![HashSet::new(); 100];
+                for from in 0..100 {
+                    for (to, _) in graph.neighbors(from) {
+                        adjacent[from].insert(to);
+                        adjacent[to].insert(from);
+                    }
+                }
+                let degree = adjacent.iter().map(HashSet::len).max().unwrap();
+                assert!(colors(&color) <= degree + 1);
+            }
+        }
+        let graph = grid(6, 6);
+        assert_eq!(colors(&graph.greedy_coloring(ColoringOrder::DSatur)), 2);
+        assert!(ASimpleGraph::<usize>::new()
+            .greedy_coloring(ColoringOrder::DSatur)
+            .is_empty());
+    }
+}
@@ -0,0 +1,195 @@
+//Compressed sparse row (CSR) copy of an ASimpleGraph for read heavy workloads.
+//Instead of one Vec per node, all edges live in two flat arrays sorted by source:
+//the edges of node i are targets[offsets[i]..offsets[i + 1]] with the weights at
+//the same positions. Three allocations in total, and walking the edges of
+//consecutive nodes walks memory front to back.
+//It can't be changed, so it's built with ASimpleGraph::freeze once the graph is done.
+//The ignored csr_vs_vec test times both layouts on a 500k node graph, run it with
+//cargo test --release -- --ignored --nocapture csr_vs_vec
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{ASimpleGraph, GraphKind, GraphView};
+
+pub struct CsrGraph<K> {
+    nodes: Vec<K>,
+    index: HashMap<K, usize>,
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    weights: Vec<u32>,
+    kind: GraphKind,
+}
+
+impl<K: Clone + Eq + Hash> ASimpleGraph<K> {
+    //Edge labels are dropped, none of the algorithms look at them
+    pub fn freeze(&self) -> CsrGraph<K> {
+        let edge_count = self.adjacency_list.iter().map(Vec::len).sum();
+        let mut offsets = Vec::with_capacity(self.nodes.len() + 1);
+        let mut targets = Vec::with_capacity(edge_count);
+        let mut weights = Vec::with_capacity(edge_count);
+        offsets.push(0);
+        for edges in &self.adjacency_list {
+            for e in edges {
+                targets.push(e.node);
+                weights.push(e.weight);
+            }
+            offsets.push(targets.len());
+        }
+        CsrGraph {
+            nodes: self.nodes.clone(),
+            index: self.index.clone(),
+            offsets,
+            targets,
+            weights,
+            kind: self.kind,
+        }
+    }
+}
+
+impl<K> CsrGraph<K> {
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    pub fn targets(&self) -> &[usize] {
+        &self.targets
+    }
+
+    pub fn weights(&self) -> &[u32] {
+        &self.weights
+    }
+
+    pub fn nodes(&self) -> &[K] {
+        &self.nodes
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+}
+
+impl<K: Clone + Eq + Hash> GraphView<K> for CsrGraph<K> {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn node(&self, i: usize) -> &K {
+        &self.nodes[i]
+    }
+
+    fn node_index(&self, node: &K) -> Option<usize> {
+        self.index.get(node).cloned()
+    }
+
+    fn neighbors(&self, i: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let range = self.offsets[i]..self.offsets[i + 1];
+        self.targets[range.clone()]
+            .iter()
+            .cloned()
+            .zip(self.weights[range].iter().cloned())
+    }
+
+    fn kind(&self) -> GraphKind {
+        self.kind
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::generate::random_graph;
+    use super::super::{ASimpleGraph, GraphKind, GraphView};
+    use std::collections::HashSet;
+    use std::time::Instant;
+
+    #[test]
+    fn layout() {
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        graph.set_nodes(vec!["a", "b", "c"]);
+        graph.set_edges("a", vec![(4, "b"), (1, "c")]);
+        let frozen = graph.freeze();
+        assert_eq!(frozen.offsets(), &[0, 2, 3, 4]);
+        assert_eq!(frozen.targets(), &[1, 2, 0, 0]);
+        assert_eq!(frozen.weights(), &[4, 1, 4, 1]);
+        assert_eq!(frozen.edge_count(), 4);
+        assert_eq!(frozen.kind(), GraphKind::Undirected);
+        assert_eq!(
+            frozen.shortest_path("b", "c"),
+            Some((5, vec!["b", "a", "c"]))
+        );
+    }
+
+    #[test]
+    fn same_answers() {
+        for seed in 0..10 {
+            let graph = random_graph(30, 60, GraphKind::Directed, seed);
+            let frozen = graph.freeze();
+            for (from, to) in [(0, 1), (2, 29), (7, 7)] {
+                assert_eq!(
+                    graph.shortest_path(from, to),
+                    frozen.shortest_path(from, to)
+                );
+                let flow = (graph.max_flow(from, to), frozen.max_flow(from, to));
+                assert_eq!(
+                    flow.0.map(|f| (f.value, f.flows)),
+                    flow.1.map(|f| (f.value, f.flows))
+                );
+            }
+            assert_eq!(graph.connected(3, 2), frozen.connected(3, 2));
+            assert_eq!(graph.kruskal(), frozen.kruskal());
+            assert_eq!(graph.prim(), frozen.prim());
+            assert_eq!(
+                graph.strongly_connected_components(),
+                frozen.strongly_connected_components()
+            );
+            let (matrix, frozen_matrix) = (graph.johnson(), frozen.floyd_warshall());
+            for i in 0..30 {
+                for j in 0..30 {
+                    assert_eq!(matrix.distance(&i, &j), frozen_matrix.distance(&i, &j));
+                }
+            }
+            let left: HashSet<usize> = (0..15).collect();
+            assert_eq!(
+                graph.bipartite_matching(&left).len(),
+                frozen.bipartite_matching(&left).len()
+            );
+        }
+    }
+
+    //Not a real test, run it with
+    //cargo test --release -- --ignored --nocapture csr_vs_vec
+    #[test]
+    #[ignore]
+    fn csr_vs_vec() {
+        fn time<G: GraphView<usize>>(name: &str, graph: &G) {
+            let start = Instant::now();
+            let mut total = 0u64;
+            for _ in 0..20 {
+                for i in 0..graph.node_count() {
+                    total += graph.neighbors(i).map(|(_, w)| w as u64).sum::<u64>();
+                }
+            }
+            let scan = start.elapsed();
+            let start = Instant::now();
+            let components = graph.strongly_connected_components().len();
+            let scc = start.elapsed();
+            let start = Instant::now();
+            let (cost, tree) = graph.kruskal();
+            let mst = start.elapsed();
+            println!(
+                "{:>10}: 20 edge scans {:?}, scc {:?}, kruskal {:?} ({} {} {} {})",
+                name,
+                scan,
+                scc,
+                mst,
+                total,
+                components,
+                cost,
+                tree.len()
+            );
+        }
+        let graph = random_graph(500_000, 4_000_000, GraphKind::Directed, 42);
+        let frozen = graph.freeze();
+        time("Vec<Vec>", &graph);
+        time("CSR", &frozen);
+    }
+}
@@ -0,0 +1,301 @@
+//Eulerian paths use every edge exactly once. Euler's conditions decide if there is one:
+//  all edges are in one connected component (nodes without edges don't count)
+//  undirected: no node has an odd degree for a circuit, exactly two (the ends) for a path
+//  directed: in degree equals out degree everywhere for a circuit, except one node
+//    with one more out (the start) and one with one more in (the end) for a path
+//Hierholzer's algorithm builds it: walk unused edges until stuck, which can only
+//happen at the end. Then back up, and wherever a node still has unused edges walk
+//a detour from there. The nodes come off the backtracking in reverse order
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use super::{GraphKind, GraphView};
+use crate::union_find::UnionFind;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EulerError<K> {
+    //Nothing to walk
+    NoEdges,
+    //Two nodes with edges that aren't connected to each other, ignoring direction
+    Disconnected(K, K),
+    //Undirected graph with the wrong number of odd degree nodes, these ones
+    OddDegree(Vec<K>),
+    //Directed graph, nodes with their out degree minus in degree where that isn't allowed
+    Unbalanced(Vec<(K, i64)>),
+}
+
+impl<K: Debug> Display for EulerError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EulerError::NoEdges => write!(f, "the graph has no edges"),
+            EulerError::Disconnected(a, b) => {
+                write!(f, "{:?} and {:?} are in different components", a, b)
+            }
+            EulerError::OddDegree(nodes) => write!(f, "nodes with odd degree: {:?}", nodes),
+            EulerError::Unbalanced(nodes) => {
+                write!(f, "nodes with out degree - in degree off: {:?}", nodes)
+            }
+        }
+    }
+}
+
+impl<K: Debug> std::error::Error for EulerError<K> {}
+
+impl EulerError<usize> {
+    pub(super) fn map<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+        self,
+        graph: &G,
+    ) -> EulerError<K> {
+        let key = |i: usize| graph.node(i).clone();
+        match self {
+            EulerError::NoEdges => EulerError::NoEdges,
+            EulerError::Disconnected(a, b) => EulerError::Disconnected(key(a), key(b)),
+            EulerError::OddDegree(nodes) => {
+                EulerError::OddDegree(nodes.into_iter().map(key).collect())
+            }
+            EulerError::Unbalanced(nodes) => {
+                EulerError::Unbalanced(nodes.into_iter().map(|(i, d)| (key(i), d)).collect())
+            }
+        }
+    }
+}
+
+//Finds a path, or a circuit if closed is set. A path comes out as a circuit
+//whenever the graph has one
+pub(super) fn euler<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    closed: bool,
+) -> Result<Vec<usize>, EulerError<usize>> {
+    let n = graph.node_count();
+    let directed = graph.kind() == GraphKind::Directed;
+    //Every edge once with an id to mark it used. An undirected edge is taken from its
+    //lower end, self loops are only stored once anyway
+    let mut adjacency: Vec<Vec<(usize, usize)>> = vec![vec![]; n];
+    let mut edges = 0;
+    //Out degree minus in degree, or just the degree if undirected
+    let mut balance = vec![0i64; n];
+    for from in 0..n {
+        for (to, _) in graph.neighbors(from) {
+            if !directed && to < from {
+                continue;
+            }
+            adjacency[from].push((to, edges));
+            if directed {
+                balance[from] += 1;
+                balance[to] -= 1;
+            } else {
+                if to != from {
+                    adjacency[to].push((from, edges));
+                }
+                balance[from] += 1;
+                balance[to] += 1;
+            }
+            edges += 1;
+        }
+    }
+    if edges == 0 {
+        return Err(EulerError::NoEdges);
+    }
+
+    let start = if directed {
+        let off: Vec<(usize, i64)> = (0..n)
+            .filter(|&i| balance[i] != 0)
+            .map(|i| (i, balance[i]))
+            .collect();
+        let path_ends = off.len() == 2 && off.iter().all(|&(_, d)| d.abs() == 1);
+        if !off.is_empty() && (closed || !path_ends) {
+            return Err(EulerError::Unbalanced(off));
+        }
+        off.iter().find(|&&(_, d)| d == 1).map(|&(i, _)| i)
+    } else {
+        let odd: Vec<usize> = (0..n).filter(|&i| balance[i] % 2 != 0).collect();
+        if !odd.is_empty() && (closed || odd.len() != 2) {
+            return Err(EulerError::OddDegree(odd));
+        }
+        odd.first().cloned()
+    };
+    let start = start.unwrap_or_else(|| (0..n).find(|&i| !adjacency[i].is_empty()).unwrap());
+
+    //Directed graphs can have nodes with only incoming edges, those count too
+    let mut components = UnionFind::new(n);
+    let mut touched = vec![false; n];
+    for (from, list) in adjacency.iter().enumerate() {
+        for &(to, _) in list {
+            components.union(from, to);
+            touched[from] = true;
+            touched[to] = true;
+        }
+    }
+    if let Some(other) = (0..n).find(|&i| touched[i] && !components.connected(start, i)) {
+        return Err(EulerError::Disconnected(start, other));
+    }
+
+    let mut used = vec![false; edges];
+    //Next edge to look at for every node, so every edge is looked at once per end
+    let mut next = vec![0; n];
+    let mut stack = vec![start];
+    let mut path = Vec::with_capacity(edges + 1);
+    while let Some(&u) = stack.last() {
+        while next[u] < adjacency[u].len() && used[adjacency[u][next[u]].1] {
+            next[u] += 1;
+        }
+        match adjacency[u].get(next[u]) {
+            Some(&(v, id)) => {
+                used[id] = true;
+                stack.push(v);
+            }
+            None => {
+                path.push(u);
+                stack.pop();
+            }
+        }
+    }
+    path.reverse();
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::generate::{complete, grid};
+    use super::super::{ASimpleGraph, GraphKind, GraphView};
+    use super::EulerError;
+    use std::collections::HashMap;
+
+    //Checks the walk uses every edge exactly once
+    fn assert_walk<K: Clone + Eq + std::hash::Hash + Ord>(graph: &ASimpleGraph<K>, walk: &[K]) {
+        let directed = graph.kind() == GraphKind::Directed;
+        let key = |a: &K, b: &K| {
+            if directed || a <= b {
+                (a.clone(), b.clone())
+            } else {
+                (b.clone(), a.clone())
+            }
+        };
+        let mut left: HashMap<(K, K), usize> = HashMap::new();
+        for i in 0..graph.node_count() {
+            for (to, _) in graph.neighbors(i) {
+                let (a, b) = (graph.node(i), graph.node(to));
+                if directed || a <= b {
+                    *left.entry(key(a, b)).or_default() += 1;
+                }
+            }
+        }
+        for step in walk.windows(2) {
+            let count = left.get_mut(&key(&step[0], &step[1])).unwrap();
+            *count -= 1;
+        }
+        assert!(left.values().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn undirected() {
+        //The house of Nikolaus: a square with both diagonals and a roof
+        let mut graph = ASimpleGraph::with_kind(GraphKind::Undirected);
+        for (from, to) in [
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 1),
+            (1, 3),
+            (2, 4),
+            (3, 5),
+            (4, 5),
+        ] {
+            graph.add_edge(from, to, 1);
+        }
+        let path = graph.eulerian_path().unwrap();
+        assert_eq!(path.len(), 9);
+        assert!(path[0] == 1 || path[0] == 2);
+        assert_walk(&graph, &path);
+        let mut odd = match graph.eulerian_circuit() {
+            Err(EulerError::OddDegree(nodes)) => nodes,
+            other => panic!("{:?}", other),
+        };
+        odd.sort();
+        assert_eq!(odd, vec![1, 2]);
+
+        //Every node of K5 has degree 4, a self loop adds 2
+        let mut graph = complete(5, GraphKind::Undirected);
+        graph.add_edge(3, 3, 1);
+        let circuit = graph.eulerian_circuit().unwrap();
+        assert_eq!(circuit.len(), 12);
+        assert_eq!(circuit.first(), circuit.last());
+        assert_walk(&graph, &circuit);
+
+        //Four nodes of a 3x3 grid have degree 3
+        assert!(
+            matches!(grid(3, 3).eulerian_path(), Err(EulerError::OddDegree(n)) if n.len() == 4)
+        );
+    }
+
+    #[test]
+    fn directed() {
+        let mut graph = ASimpleGraph::new();
+        for (from, to) in [("a", "b"), ("b", "c"), ("c", "a"), ("c", "d"), ("d", "c")] {
+            graph.add_edge(from, to, 1);
+        }
+        let circuit = graph.eulerian_circuit().unwrap();
+        assert_eq!(circuit.len(), 6);
+        assert_walk(&graph, &circuit);
+
+        graph.add_edge("d", "e", 1);
+        let path = graph.eulerian_path().unwrap();
+        assert_eq!((path[0], path[path.len() - 1]), ("d", "e"));
+        assert_walk(&graph, &path);
+        assert_eq!(
+            graph.eulerian_circuit(),
+            Err(EulerError::Unbalanced(vec![("d", 1), ("e", -1)]))
+        );
+
+        //Two sinks
+        graph.add_edge("a", "f", 1);
+        assert!(matches!(graph.eulerian_path(), Err(EulerError::Unbalanced(n)) if n.len() == 4));
+    }
+
+    #[test]
+    fn multigraph() {
+        //Two parallel edges between a and b, there and back again
+        let mut graph = ASimpleGraph::multigraph(GraphKind::Undirected);
+        graph.add_labeled_edge("a", "b", 1, "x");
+        graph.add_labeled_edge("a", "b", 1, "y");
+        let circuit = graph.eulerian_circuit().unwrap();
+        assert_eq!(circuit.len(), 3);
+        assert_walk(&graph, &circuit);
+    }
+
+    #[test]
+    fn no_path() {
+        let mut graph = ASimpleGraph::<&str>::with_kind(GraphKind::Undirected);
+        graph.set_nodes(vec!["a", "b"]);
+        assert_eq!(graph.eulerian_path(), Err(EulerError::NoEdges));
+
+        //Two separate triangles, the isolated node doesn't matter
+        graph.set_nodes(vec!["lonely"]);
+        for (from, to) in [
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("x", "y"),
+            ("y", "z"),
+            ("z", "x"),
+        ] {
+            graph.add_edge(from, to, 1);
+        }
+        let error = graph.eulerian_circuit().unwrap_err();
+        assert!(matches!(error, EulerError::Disconnected("a", _)));
+        assert!(error.to_string().contains("different components"));
+    }
+
+    #[test]
+    fn deep() {
+        let n = 200_000;
+        let mut graph = ASimpleGraph::new();
+        graph.set_nodes((0..n).collect());
+        for i in 0..n {
+            graph.add_edge(i, (i + 1) % n, 1);
+        }
+        let circuit = graph.eulerian_circuit().unwrap();
+        assert_eq!(circuit.len(), n + 1);
+        assert_eq!(circuit[..3], [0, 1, 2]);
+    }
+}
@@ -0,0 +1,310 @@
+//Test graphs. The structured ones (complete, path, star, grid) are always the same,
+//the random ones are driven by Rng so the same seed gives the same graph on every run.
+//Nodes are 0..n and every edge has weight 1, randomize_weights fixes that if needed
+use std::ops::Range;
+
+use super::{ASimpleGraph, GraphKind};
+use crate::rng::Rng;
+
+fn empty(n: usize, kind: GraphKind) -> ASimpleGraph<usize> {
+    let mut graph = ASimpleGraph::with_kind(kind);
+    graph.set_nodes((0..n).collect());
+    graph
+}
+
+//Every pair of distinct nodes linked, both ways if directed
+pub fn complete(n: usize, kind: GraphKind) -> ASimpleGraph<usize> {
+    let mut graph = empty(n, kind);
+    for from in 0..n {
+        for to in 0..n {
+            if from < to || (from > to && kind == GraphKind::Directed) {
+                graph.push_edge(from, to, 1, None);
+            }
+        }
+    }
+    graph
+}
+
+//0 - 1 - 2 - ... - n-1
+pub fn path(n: usize, kind: GraphKind) -> ASimpleGraph<usize> {
+    let mut graph = empty(n, kind);
+    for i in 1..n {
+        graph.push_edge(i - 1, i, 1, None);
+    }
+    graph
+}
+
+//Node 0 in the middle, linked to every other node (edges point outwards if directed)
+pub fn star(n: usize, kind: GraphKind) -> ASimpleGraph<usize> {
+    let mut graph = empty(n, kind);
+    for i in 1..n {
+        graph.push_edge(0, i, 1, None);
+    }
+    graph
+}
+
+//Undirected rows x cols lattice, the node in row r and column c is r * cols + c
+pub fn grid(rows: usize, cols: usize) -> ASimpleGraph<usize> {
+    let mut graph = empty(rows * cols, GraphKind::Undirected);
+    for r in 0..rows {
+        for c in 0..cols {
+            let i = r * cols + c;
+            if c + 1 < cols {
+                graph.push_edge(i, i + 1, 1, None);
+            }
+            if r + 1 < rows {
+                graph.push_edge(i, i + cols, 1, None);
+            }
+        }
+    }
+    graph
+}
+
+//Calls f with the positions in 0.. that come up when every position is picked with
+//probability p on its own, until f returns false. Rather than a coin flip per position
+//it jumps straight to the next pick: the gap between picks is geometric, and
+//floor(ln(1 - r) / ln(1 - p)) for a uniform r has exactly that distribution.
+//That makes sparse random graphs O(V + E) instead of O(V^2) (Batagelj and Brandes).
+//ln_1p keeps ln(1 - p) from rounding to 0 when p is tiny, which would make every gap 0
+//instead of huge
+fn sample(p: f64, rng: &mut Rng, mut f: impl FnMut(usize) -> bool) {
+    if p <= 0.0 {
+        return;
+    }
+    let mut position: usize = 0;
+    loop {
+        if p < 1.0 {
+            let gap = ((-rng.next_f64()).ln_1p() / (-p).ln_1p()).floor();
+            //Past the end of any graph, or NaN for a p outside [0, 1]
+            if gap.is_nan() || gap >= usize::MAX as f64 {
+                return;
+            }
+            match position.checked_add(gap as usize) {
+                Some(next) => position = next,
+                None => return,
+            }
+        }
+        if !f(position) {
+            return;
+        }
+        position += 1;
+    }
+}
+
+//G(n, p): each of the possible edges is there with probability p, no self loops
+pub fn erdos_renyi(n: usize, p: f64, kind: GraphKind, rng: &mut Rng) -> ASimpleGraph<usize> {
+    let mut graph = empty(n, kind);
+    //Pairs are numbered row by row. Undirected row v holds the v pairs (v, w < v),
+    //directed row v the n - 1 pairs (v, w != v)
+    let row = |v: usize| match kind {
+        GraphKind::Undirected => v,
+        GraphKind::Directed => n - 1,
+    };
+    let (mut v, mut start) = (0, 0);
+    sample(p, rng, |position| {
+        while v < n && position - start >= row(v) {
+            start += row(v);
+            v += 1;
+        }
+        if v == n {
+            return false;
+        }
+        let w = position - start;
+        let to = if kind == GraphKind::Directed && w >= v {
+            w + 1
+        } else {
+            w
+        };
+        graph.push_edge(v, to, 1, None);
+        true
+    });
+    graph
+}
+
+//Preferential attachment: nodes arrive one at a time and link to m distinct older
+//nodes, picked with probability proportional to their degree. Gives the few big hubs
+//and long tail of real networks. The first m nodes start out without edges.
+//Picking by degree is a uniform pick from a list where every node shows up once per edge
+pub fn barabasi_albert(n: usize, m: usize, rng: &mut Rng) -> ASimpleGraph<usize> {
+    let mut graph = empty(n, GraphKind::Undirected);
+    if m == 0 {
+        return graph;
+    }
+    let mut targets: Vec<usize> = (0..m.min(n)).collect();
+    let mut repeated = vec![];
+    for source in m..n {
+        for &t in &targets {
+            graph.push_edge(source, t, 1, None);
+        }
+        repeated.extend(targets.iter().cloned());
+        repeated.extend(std::iter::repeat_n(source, m));
+        targets.clear();
+        while targets.len() < m {
+            let t = repeated[rng.below(repeated.len())];
+            if !targets.contains(&t) {
+                targets.push(t);
+            }
+        }
+    }
+    graph
+}
+
+//Directed, each edge i -> j with i < j is there with probability p. Edges only ever
+//go up, so 0..n is a topological order and there can't be a cycle
+pub fn random_dag(n: usize, p: f64, rng: &mut Rng) -> ASimpleGraph<usize> {
+    let mut graph = empty(n, GraphKind::Directed);
+    //Same numbering as an undirected erdos_renyi, just pointed from low to high
+    let (mut v, mut start) = (0, 0);
+    sample(p, rng, |position| {
+        while v < n && position - start >= v {
+            start += v;
+            v += 1;
+        }
+        if v == n {
+            return false;
+        }
+        graph.push_edge(position - start, v, 1, None);
+        true
+    });
+    graph
+}
+
+//New weights for every edge, uniform in range. Both halves of an undirected edge
+//get the same one. Panics if range is empty, there would be no weight to pick
+pub fn randomize_weights(graph: &mut ASimpleGraph<usize>, range: Range<u32>, rng: &mut Rng) {
+    assert!(
+        range.start < range.end,
+        "randomize_weights: empty weight range {:?}",
+        range
+    );
+    let span = (range.end - range.start) as usize;
+    for from in 0..graph.nodes.len() {
+        for p in 0..graph.adjacency_list[from].len() {
+            let to = graph.adjacency_list[from][p].node;
+            if graph.kind == GraphKind::Undirected && to < from {
+                continue;
+            }
+            let weight = range.start + rng.below(span) as u32;
+            let label = graph.adjacency_list[from][p].label.clone();
+            graph.adjacency_list[from][p].weight = weight;
+            if graph.kind == GraphKind::Undirected {
+                if let Some(m) = graph.find_edge(to, from, &label) {
+                    graph.adjacency_list[to][m].weight = weight;
+                }
+            }
+        }
+    }
+}
+
+//About m random edges between n nodes with weights in 0..100, the other modules use
+//it to check that two ways of computing something agree
+#[cfg(test)]
+pub(super) fn random_graph(n: usize, m: usize, kind: GraphKind, seed: u64) -> ASimpleGraph<usize> {
+    let pairs = match kind {
+        GraphKind::Directed => n * n.saturating_sub(1),
+        GraphKind::Undirected => n * n.saturating_sub(1) / 2,
+    };
+    let mut rng = Rng::new(seed);
+    let mut graph = erdos_renyi(n, m as f64 / pairs.max(1) as f64, kind, &mut rng);
+    randomize_weights(&mut graph, 0..100, &mut rng);
+    graph
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{GraphKind, GraphView};
+    use super::*;
+
+    fn edge_count(graph: &ASimpleGraph<usize>) -> usize {
+        (0..graph.node_count())
+            .map(|i| graph.neighbors(i).count())
+            .sum()
+    }
+
+    #[test]
+    fn structured() {
+        let graph = complete(6, GraphKind::Undirected);
+        assert_eq!(edge_count(&graph), 2 * 15);
+        assert_eq!(edge_count(&complete(6, GraphKind::Directed)), 30);
+
+        let graph = path(5, GraphKind::Directed);
+        assert_eq!(graph.shortest_path(0, 4), Some((4, vec![0, 1, 2, 3, 4])));
+        assert_eq!(graph.shortest_path(4, 0), None);
+
+        let graph = star(5, GraphKind::Undirected);
+        assert_eq!(graph.neighbors(0).count(), 4);
+        assert_eq!(graph.articulation_points().len(), 1);
+
+        let graph = grid(3, 4);
+        assert_eq!(edge_count(&graph), 2 * (3 * 3 + 2 * 4));
+        assert_eq!(graph.shortest_path(0, 11).map(|p| p.0), Some(5));
+        assert_eq!(edge_count(&grid(0, 4)), 0);
+    }
+
+    #[test]
+    fn erdos_renyi_graphs() {
+        for kind in [GraphKind::Directed, GraphKind::Undirected] {
+            let graph = erdos_renyi(300, 0.05, kind, &mut Rng::new(1));
+            let again = erdos_renyi(300, 0.05, kind, &mut Rng::new(1));
+            assert_eq!(graph.adjacency_list, again.adjacency_list);
+
+            //About 0.05 of the 300 * 299 ordered pairs, no self loops
+            let expected = 0.05 * 300.0 * 299.0;
+            let edges = edge_count(&graph) as f64;
+            assert!((edges - expected).abs() < expected * 0.05);
+            assert!((0..300).all(|i| graph.neighbors(i).all(|(to, _)| to != i)));
+        }
+        assert_eq!(
+            edge_count(&erdos_renyi(50, 0.0, GraphKind::Directed, &mut Rng::new(1))),
+            0
+        );
+        //ln(1 - p) is 0 in floating point here, which must not turn into "every pair"
+        let tiny = erdos_renyi(50, 1e-17, GraphKind::Directed, &mut Rng::new(1));
+        assert_eq!(edge_count(&tiny), 0);
+        let full = erdos_renyi(50, 1.0, GraphKind::Directed, &mut Rng::new(1));
+        assert_eq!(
+            full.adjacency_list,
+            complete(50, GraphKind::Directed).adjacency_list
+        );
+    }
+
+    #[test]
+    fn barabasi_albert_graphs() {
+        let (n, m) = (2000, 3);
+        let graph = barabasi_albert(n, m, &mut Rng::new(5));
+        assert_eq!(edge_count(&graph), 2 * m * (n - m));
+        //A spanning tree that reaches every node
+        assert_eq!(graph.kruskal().1.len(), n - 1);
+        //Early nodes collect far more edges than the average of 2m
+        let biggest = (0..n).map(|i| graph.neighbors(i).count()).max().unwrap();
+        assert!(biggest > 10 * 2 * m);
+    }
+
+    #[test]
+    fn random_dags() {
+        let graph = random_dag(200, 0.1, &mut Rng::new(3));
+        assert!(edge_count(&graph) > 0);
+        assert!((0..200).all(|i| graph.neighbors(i).all(|(to, _)| to > i)));
+        assert_eq!(graph.strongly_connected_components().len(), 200);
+        assert_eq!(edge_count(&random_dag(50, 1e-17, &mut Rng::new(3))), 0);
+    }
+
+    #[test]
+    fn weights() {
+        let mut graph = grid(10, 10);
+        randomize_weights(&mut graph, 5..10, &mut Rng::new(9));
+        for from in 0..100 {
+            for (to, weight) in graph.neighbors(from) {
+                assert!((5..10).contains(&weight));
+                assert!(graph.neighbors(to).any(|e| e == (from, weight)));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "empty weight range")]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn reversed_weights() {
+        randomize_weights(&mut path(3, GraphKind::Directed), 10..5, &mut Rng::new(9));
+    }
+}
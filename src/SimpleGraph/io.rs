@@ -0,0 +1,935 @@
+//Readers and writers for three plain text formats:
+//  Graphviz DOT:   strict digraph { "a" -> "b" [weight=4]; }
+//  Edge list CSV:  from,to,weight rows. A row with empty to and weight declares a node
+//                  without outgoing edges, so isolated nodes survive a round trip
+//  JSON adjacency: { "a": [{"to": "b", "weight": 4}], "b": [] }
+//Writers need K: Display, readers build the keys back up with K: FromStr.
+//Nodes come back in the order they first show up, edges in the order they are listed.
+//Only DOT knows about graph kinds and edge labels, the other two always read
+//into a simple directed graph
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+use super::{csv_field, ASimpleGraph, GraphKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+//Walks the input one char at a time and keeps track of where it is (1-based),
+//so every parser can point at the exact spot that went wrong
+struct Cursor<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(input: &'a str) -> Self {
+        Cursor {
+            chars: input.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().cloned()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn position(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        error_at(self.position(), message)
+    }
+
+    //Errors for a missing char point at whatever is there instead
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => self.error(&format!("expected '{}', found '{}'", expected, c)),
+            None => self.error(&format!("expected '{}', found end of input", expected)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+}
+
+//A piece of input along with the line and column it started at
+type Spanned<T> = (T, (usize, usize));
+
+fn error_at<T>((line, column): (usize, usize), message: &str) -> Result<T, ParseError> {
+    Err(ParseError {
+        line,
+        column,
+        message: message.to_string(),
+    })
+}
+
+fn parse_key<K: FromStr>(key: &str, at: (usize, usize)) -> Result<K, ParseError> {
+    match key.parse() {
+        Ok(key) => Ok(key),
+        Err(_) => error_at(at, &format!("invalid node key \"{}\"", key)),
+    }
+}
+
+fn parse_weight(weight: &str, at: (usize, usize)) -> Result<u32, ParseError> {
+    match weight.trim().parse() {
+        Ok(weight) => Ok(weight),
+        Err(_) => error_at(at, &format!("invalid weight \"{}\"", weight)),
+    }
+}
+
+//DOT ids are always written quoted, so any key survives. Only quotes and
+//backslashes need escaping
+fn dot_id(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl<K: Clone + Eq + Hash + Display> ASimpleGraph<K> {
+    pub fn to_dot(&self) -> String {
+        //strict is DOT's way of saying there are no parallel edges
+        let (header, op) = match self.kind {
+            GraphKind::Directed => ("digraph", "->"),
+            GraphKind::Undirected => ("graph", "--"),
+        };
+        let strict = if self.multigraph { "" } else { "strict " };
+        let mut dot = format!("{}{} {{\n", strict, header);
+        for n in &self.nodes {
+            dot.push_str(&format!("    {};\n", dot_id(&n.to_string())));
+        }
+        for (from, edges) in self.adjacency_list.iter().enumerate() {
+            for e in edges {
+                //Undirected edges are stored at both ends but written once
+                if self.kind == GraphKind::Undirected && e.node < from {
+                    continue;
+                }
+                let label = match &e.label {
+                    Some(label) => format!(", label={}", dot_id(label)),
+                    None => String::new(),
+                };
+                dot.push_str(&format!(
+                    "    {} {} {} [weight={}{}];\n",
+                    dot_id(&self.nodes[from].to_string()),
+                    op,
+                    dot_id(&self.nodes[e.node].to_string()),
+                    e.weight,
+                    label
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_edge_list(&self) -> String {
+        let mut csv = String::from("from,to,weight\n");
+        for (from, edges) in self.adjacency_list.iter().enumerate() {
+            let from_field = csv_field(&self.nodes[from].to_string());
+            if edges.is_empty() {
+                csv.push_str(&format!("{},,\n", from_field));
+            }
+            for e in edges {
+                csv.push_str(&format!(
+                    "{},{},{}\n",
+                    from_field,
+                    csv_field(&self.nodes[e.node].to_string()),
+                    e.weight
+                ));
+            }
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{");
+        for (i, (n, edges)) in self.nodes.iter().zip(&self.adjacency_list).enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(&format!("\n  {}: [", json_string(&n.to_string())));
+            for (j, e) in edges.iter().enumerate() {
+                if j > 0 {
+                    json.push_str(", ");
+                }
+                json.push_str(&format!(
+                    "{{\"to\": {}, \"weight\": {}}}",
+                    json_string(&self.nodes[e.node].to_string()),
+                    e.weight
+                ));
+            }
+            json.push(']');
+        }
+        json.push_str("\n}\n");
+        json
+    }
+}
+
+impl<K: Clone + Eq + Hash + FromStr> ASimpleGraph<K> {
+    //Reads the subset of DOT that describes a graph's shape: node and edge statements,
+    //chained edges (a -> b -> c), and weight (1 if missing) and label attributes on edges.
+    //Other attributes, attribute defaults (node [..]) and comments are skipped.
+    //graph gives an undirected graph, digraph a directed one. Without strict the
+    //result is a multigraph, where parallel edges need distinct labels to be kept apart
+    pub fn from_dot(input: &str) -> Result<Self, ParseError> {
+        let mut tokens = DotTokens::new(input);
+
+        let strict = tokens.peek()?.0 == DotToken::Id("strict".to_string());
+        if strict {
+            tokens.next()?;
+        }
+        let directed = match tokens.next()? {
+            (DotToken::Id(kw), _) if kw == "digraph" => true,
+            (DotToken::Id(kw), _) if kw == "graph" => false,
+            (_, at) => return error_at(at, "expected \"graph\" or \"digraph\""),
+        };
+        let kind = if directed {
+            GraphKind::Directed
+        } else {
+            GraphKind::Undirected
+        };
+        let mut graph = if strict {
+            ASimpleGraph::with_kind(kind)
+        } else {
+            ASimpleGraph::multigraph(kind)
+        };
+        //Optional graph name
+        if let DotToken::Id(_) = tokens.peek()?.0 {
+            tokens.next()?;
+        }
+        tokens.expect("{")?;
+
+        loop {
+            let (first, at) = match tokens.next()? {
+                (DotToken::Symbol("}"), _) => break,
+                (DotToken::Symbol(";"), _) => continue,
+                (DotToken::Id(first), at) => (first, at),
+                (DotToken::End, at) => return error_at(at, "expected '}', found end of input"),
+                (DotToken::Symbol(s), at) => return error_at(at, &format!("unexpected '{}'", s)),
+            };
+            //Graph attribute such as rankdir=LR
+            if tokens.peek()?.0 == DotToken::Symbol("=") {
+                tokens.next()?;
+                tokens.id()?;
+                continue;
+            }
+            if first == "node" || first == "edge" || first == "graph" {
+                tokens.expect("[")?;
+                tokens.attributes()?;
+                continue;
+            }
+            let mut chain = vec![(first, at)];
+            loop {
+                let (op, op_at) = match tokens.peek()? {
+                    (DotToken::Symbol(op), at) if op == "->" || op == "--" => (op, at),
+                    _ => break,
+                };
+                if (op == "->") != directed {
+                    return error_at(
+                        op_at,
+                        &format!("'{}' does not belong in this kind of graph", op),
+                    );
+                }
+                tokens.next()?;
+                chain.push(tokens.id()?);
+            }
+            let mut weight = 1;
+            let mut label = None;
+            if tokens.peek()?.0 == DotToken::Symbol("[") {
+                tokens.next()?;
+                for (name, (value, value_at)) in tokens.attributes()? {
+                    match name.as_str() {
+                        "weight" => weight = parse_weight(&value, value_at)?,
+                        "label" => label = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            let mut ids = vec![];
+            for (id, id_at) in chain {
+                let key = parse_key(&id, id_at)?;
+                ids.push(graph.insert_node(key));
+            }
+            for pair in ids.windows(2) {
+                graph.push_edge(pair[0], pair[1], weight, label.clone());
+            }
+        }
+        match tokens.next()? {
+            (DotToken::End, _) => Ok(graph),
+            (_, at) => error_at(at, "unexpected input after '}'"),
+        }
+    }
+
+    pub fn from_edge_list(input: &str) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(input);
+        let mut graph = ASimpleGraph::new();
+        let mut first = true;
+        loop {
+            let row_at = cursor.position();
+            let row = match csv_row(&mut cursor)? {
+                Some(row) => row,
+                None => break,
+            };
+            let header = first
+                && row
+                    .iter()
+                    .map(|f| f.0.as_str())
+                    .eq(["from", "to", "weight"]);
+            first = false;
+            if header || (row.len() == 1 && row[0].0.trim().is_empty()) {
+                continue;
+            }
+            if row.len() != 3 {
+                return error_at(
+                    row_at,
+                    &format!("expected 3 fields (from,to,weight), found {}", row.len()),
+                );
+            }
+            let from = graph.insert_node(parse_key(&row[0].0, row[0].1)?);
+            if row[1].0.is_empty() && row[2].0.trim().is_empty() {
+                continue;
+            }
+            let to = graph.insert_node(parse_key(&row[1].0, row[1].1)?);
+            let weight = parse_weight(&row[2].0, row[2].1)?;
+            graph.push_edge(from, to, weight, None);
+        }
+        Ok(graph)
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(input);
+        let mut graph = ASimpleGraph::new();
+        //Edges may point at nodes listed further down, so they are resolved at the end
+        let mut pending = vec![];
+        cursor.skip_whitespace();
+        cursor.expect('{')?;
+        cursor.skip_whitespace();
+        if cursor.peek() == Some('}') {
+            cursor.bump();
+        } else {
+            loop {
+                cursor.skip_whitespace();
+                let key_at = cursor.position();
+                let key = json_string_literal(&mut cursor)?;
+                let from = graph.insert_node(parse_key(&key, key_at)?);
+                cursor.skip_whitespace();
+                cursor.expect(':')?;
+                cursor.skip_whitespace();
+                cursor.expect('[')?;
+                cursor.skip_whitespace();
+                if cursor.peek() == Some(']') {
+                    cursor.bump();
+                } else {
+                    loop {
+                        cursor.skip_whitespace();
+                        let (to, weight) = json_edge(&mut cursor)?;
+                        pending.push((from, to, weight));
+                        cursor.skip_whitespace();
+                        match cursor.peek() {
+                            Some(',') => {
+                                cursor.bump();
+                            }
+                            Some(']') => {
+                                cursor.bump();
+                                break;
+                            }
+                            _ => return cursor.error("expected ',' or ']'"),
+                        }
+                    }
+                }
+                cursor.skip_whitespace();
+                match cursor.peek() {
+                    Some(',') => {
+                        cursor.bump();
+                    }
+                    Some('}') => {
+                        cursor.bump();
+                        break;
+                    }
+                    _ => return cursor.error("expected ',' or '}'"),
+                }
+            }
+        }
+        cursor.skip_whitespace();
+        if cursor.peek().is_some() {
+            return cursor.error("unexpected input after '}'");
+        }
+        for (from, (to, to_at), weight) in pending {
+            let key: K = parse_key(&to, to_at)?;
+            match graph.get_node_index(&key) {
+                Some(to) => {
+                    graph.push_edge(from, to, weight, None);
+                }
+                None => return error_at(to_at, &format!("unknown node \"{}\"", to)),
+            }
+        }
+        Ok(graph)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DotToken {
+    Id(String),
+    Symbol(&'static str),
+    End,
+}
+
+struct DotTokens<'a> {
+    cursor: Cursor<'a>,
+    peeked: Option<Spanned<DotToken>>,
+}
+
+impl<'a> DotTokens<'a> {
+    fn new(input: &'a str) -> Self {
+        DotTokens {
+            cursor: Cursor::new(input),
+            peeked: None,
+        }
+    }
+
+    fn peek(&mut self) -> Result<Spanned<DotToken>, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read()?);
+        }
+        Ok(self.peeked.clone().unwrap())
+    }
+
+    fn next(&mut self) -> Result<Spanned<DotToken>, ParseError> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.read(),
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ParseError> {
+        match self.next()? {
+            (DotToken::Symbol(s), _) if s == symbol => Ok(()),
+            (_, at) => error_at(at, &format!("expected '{}'", symbol)),
+        }
+    }
+
+    fn id(&mut self) -> Result<Spanned<String>, ParseError> {
+        match self.next()? {
+            (DotToken::Id(id), at) => Ok((id, at)),
+            (_, at) => error_at(at, "expected an id"),
+        }
+    }
+
+    //Skips whitespace and all three comment styles
+    fn skip_trivia(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.cursor.skip_whitespace();
+            let mut ahead = self.cursor.chars.clone();
+            match (ahead.next(), ahead.next()) {
+                (Some('#'), _) if self.cursor.column == 1 => self.skip_line(),
+                (Some('/'), Some('/')) => self.skip_line(),
+                (Some('/'), Some('*')) => {
+                    let at = self.cursor.position();
+                    self.cursor.bump();
+                    self.cursor.bump();
+                    let mut star = false;
+                    loop {
+                        match self.cursor.bump() {
+                            Some('/') if star => break,
+                            Some(c) => star = c == '*',
+                            None => return error_at(at, "unterminated comment"),
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while self.cursor.peek().is_some_and(|c| c != '\n') {
+            self.cursor.bump();
+        }
+    }
+
+    fn read(&mut self) -> Result<Spanned<DotToken>, ParseError> {
+        self.skip_trivia()?;
+        let at = self.cursor.position();
+        let c = match self.cursor.peek() {
+            Some(c) => c,
+            None => return Ok((DotToken::End, at)),
+        };
+        let token = match c {
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' => {
+                self.cursor.bump();
+                DotToken::Symbol(match c {
+                    '{' => "{",
+                    '}' => "}",
+                    '[' => "[",
+                    ']' => "]",
+                    ';' => ";",
+                    ',' => ",",
+                    _ => "=",
+                })
+            }
+            '-' if matches!(self.cursor.chars.clone().nth(1), Some('>') | Some('-')) => {
+                self.cursor.bump();
+                match self.cursor.bump() {
+                    Some('>') => DotToken::Symbol("->"),
+                    _ => DotToken::Symbol("--"),
+                }
+            }
+            '"' => {
+                self.cursor.bump();
+                let mut id = String::new();
+                loop {
+                    match self.cursor.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.cursor.bump() {
+                            Some(c @ '"') | Some(c @ '\\') => id.push(c),
+                            //Line continuation
+                            Some('\n') => {}
+                            Some(c) => {
+                                id.push('\\');
+                                id.push(c);
+                            }
+                            None => return error_at(at, "unterminated string"),
+                        },
+                        Some(c) => id.push(c),
+                        None => return error_at(at, "unterminated string"),
+                    }
+                }
+                DotToken::Id(id)
+            }
+            //Numeral, such as -1 or 2.5
+            c if c == '-' || c == '.' || c.is_ascii_digit() => {
+                let mut id = String::new();
+                if c == '-' {
+                    id.push(c);
+                    self.cursor.bump();
+                }
+                while let Some(c) = self.cursor.peek() {
+                    if c.is_ascii_digit() || c == '.' {
+                        id.push(c);
+                        self.cursor.bump();
+                    } else {
+                        break;
+                    }
+                }
+                DotToken::Id(id)
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut id = String::new();
+                while let Some(c) = self.cursor.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        id.push(c);
+                        self.cursor.bump();
+                    } else {
+                        break;
+                    }
+                }
+                DotToken::Id(id)
+            }
+            c => return self.cursor.error(&format!("unexpected '{}'", c)),
+        };
+        Ok((token, at))
+    }
+
+    //Reads an attribute list after its '[' up to and including ']'
+    fn attributes(&mut self) -> Result<Vec<(String, Spanned<String>)>, ParseError> {
+        let mut attributes = vec![];
+        loop {
+            match self.next()? {
+                (DotToken::Symbol("]"), _) => return Ok(attributes),
+                (DotToken::Symbol(","), _) | (DotToken::Symbol(";"), _) => {}
+                (DotToken::Id(name), _) => {
+                    self.expect("=")?;
+                    attributes.push((name, self.id()?));
+                }
+                (DotToken::End, at) => return error_at(at, "expected ']', found end of input"),
+                (_, at) => return error_at(at, "expected attribute name"),
+            }
+        }
+    }
+}
+
+//One CSV row, None at the end of input. Every field comes with its position.
+//Quoted fields may hold commas, newlines and doubled quotes
+fn csv_row(cursor: &mut Cursor<'_>) -> Result<Option<Vec<Spanned<String>>>, ParseError> {
+    if cursor.peek().is_none() {
+        return Ok(None);
+    }
+    let mut row = vec![];
+    loop {
+        let at = cursor.position();
+        let mut field = String::new();
+        if cursor.peek() == Some('"') {
+            cursor.bump();
+            loop {
+                match cursor.bump() {
+                    Some('"') if cursor.peek() == Some('"') => {
+                        cursor.bump();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return error_at(at, "unterminated quoted field"),
+                }
+            }
+            match cursor.peek() {
+                Some(',') | Some('\n') | Some('\r') | None => {}
+                Some(c) => return cursor.error(&format!("unexpected '{}' after quoted field", c)),
+            }
+        } else {
+            while let Some(c) = cursor.peek() {
+                if c == ',' || c == '\n' || c == '\r' {
+                    break;
+                }
+                field.push(c);
+                cursor.bump();
+            }
+        }
+        row.push((field, at));
+        match cursor.bump() {
+            Some(',') => {}
+            Some('\r') => {
+                if cursor.peek() == Some('\n') {
+                    cursor.bump();
+                }
+                return Ok(Some(row));
+            }
+            _ => return Ok(Some(row)),
+        }
+    }
+}
+
+fn json_string_literal(cursor: &mut Cursor<'_>) -> Result<String, ParseError> {
+    let at = cursor.position();
+    cursor.expect('"')?;
+    let mut s = String::new();
+    loop {
+        match cursor.bump() {
+            Some('"') => return Ok(s),
+            Some('\\') => {
+                let escape_at = cursor.position();
+                match cursor.bump() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        let high = json_hex4(cursor)?;
+                        let code = if (0xd800..0xdc00).contains(&high) {
+                            //Surrogate pair, the low half has to follow right away
+                            cursor.expect('\\')?;
+                            cursor.expect('u')?;
+                            let low = json_hex4(cursor)?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return error_at(escape_at, "invalid surrogate pair");
+                            }
+                            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                        } else {
+                            high
+                        };
+                        match char::from_u32(code) {
+                            Some(c) => s.push(c),
+                            None => return error_at(escape_at, "invalid unicode escape"),
+                        }
+                    }
+                    _ => return error_at(escape_at, "invalid escape"),
+                }
+            }
+            Some(c) if (c as u32) < 0x20 => {
+                return error_at(at, "control character in string");
+            }
+            Some(c) => s.push(c),
+            None => return error_at(at, "unterminated string"),
+        }
+    }
+}
+
+fn json_hex4(cursor: &mut Cursor<'_>) -> Result<u32, ParseError> {
+    let at = cursor.position();
+    let mut code = 0;
+    for _ in 0..4 {
+        match cursor.bump().and_then(|c| c.to_digit(16)) {
+            Some(d) => code = code * 16 + d,
+            None => return error_at(at, "expected 4 hex digits"),
+        }
+    }
+    Ok(code)
+}
+
+//{"to": "b", "weight": 4}, keys in any order. The target comes back with its
+//position so an unknown node can be reported where it was named
+fn json_edge(cursor: &mut Cursor<'_>) -> Result<(Spanned<String>, u32), ParseError> {
+    let at = cursor.position();
+    cursor.expect('{')?;
+    let mut to = None;
+    let mut weight = None;
+    loop {
+        cursor.skip_whitespace();
+        let key_at = cursor.position();
+        let key = json_string_literal(cursor)?;
+        cursor.skip_whitespace();
+        cursor.expect(':')?;
+        cursor.skip_whitespace();
+        match key.as_str() {
+            "to" => {
+                let to_at = cursor.position();
+                to = Some((json_string_literal(cursor)?, to_at));
+            }
+            "weight" => {
+                let weight_at = cursor.position();
+                let mut digits = String::new();
+                while let Some(c) = cursor.peek() {
+                    if c.is_ascii_digit()
+                        || c == '-'
+                        || c == '.'
+                        || c == 'e'
+                        || c == 'E'
+                        || c == '+'
+                    {
+                        digits.push(c);
+                        cursor.bump();
+                    } else {
+                        break;
+                    }
+                }
+                weight = Some(parse_weight(&digits, weight_at)?);
+            }
+            _ => return error_at(key_at, &format!("unknown edge field \"{}\"", key)),
+        }
+        cursor.skip_whitespace();
+        match cursor.peek() {
+            Some(',') => {
+                cursor.bump();
+            }
+            Some('}') => {
+                cursor.bump();
+                break;
+            }
+            _ => return cursor.error("expected ',' or '}'"),
+        }
+    }
+    match (to, weight) {
+        (Some(to), Some(weight)) => Ok((to, weight)),
+        (None, _) => error_at(at, "edge is missing \"to\""),
+        (_, None) => error_at(at, "edge is missing \"weight\""),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{ASimpleGraph, GraphKind};
+    use super::ParseError;
+    use crate::rng::Rng;
+    use std::collections::HashMap;
+
+    //A few dozen seeds are enough to shake out the escaping and the edge cases
+    fn random_graph(seed: u64, kind: GraphKind, multigraph: bool) -> ASimpleGraph<String> {
+        let mut rng = Rng::new(seed);
+        //Keys with the characters every format has to escape
+        let names = [
+            "a",
+            "b c",
+            "quote\"d",
+            "comma,",
+            "back\\slash",
+            "new\nline",
+            "ünï",
+            "42",
+        ];
+        let labels = ["x", "y \"z\"", "w,v"];
+        let n = 1 + rng.below(names.len());
+        let mut graph = if multigraph {
+            ASimpleGraph::multigraph(kind)
+        } else {
+            ASimpleGraph::with_kind(kind)
+        };
+        graph.set_nodes(names[..n].iter().map(|s| s.to_string()).collect());
+        for from in 0..n {
+            for _ in 0..rng.below(4) {
+                let (weight, to) = (rng.below(100) as u32, names[rng.below(n)]);
+                if multigraph {
+                    let label = labels[rng.below(labels.len())];
+                    graph.add_labeled_edge(names[from].to_string(), to.to_string(), weight, label);
+                } else {
+                    graph.add_edge(names[from].to_string(), to.to_string(), weight);
+                }
+            }
+        }
+        graph
+    }
+
+    //Same kind, same nodes, and every node has the same edges. Order may differ,
+    //an edge list names targets before their own rows and undirected edges get
+    //mirrored in whatever order they are read back
+    fn assert_same(a: &ASimpleGraph<String>, b: &ASimpleGraph<String>) {
+        type Edges = HashMap<String, Vec<(u32, String, Option<String>)>>;
+        let edges = |g: &ASimpleGraph<String>| -> Edges {
+            g.nodes
+                .iter()
+                .zip(&g.adjacency_list)
+                .map(|(n, edges)| {
+                    let mut edges: Vec<_> = edges
+                        .iter()
+                        .map(|e| (e.weight, g.nodes[e.node].clone(), e.label.clone()))
+                        .collect();
+                    edges.sort();
+                    (n.clone(), edges)
+                })
+                .collect()
+        };
+        assert_eq!(a.kind(), b.kind());
+        assert_eq!(a.is_multigraph(), b.is_multigraph());
+        assert_eq!(a.nodes.len(), b.nodes.len());
+        assert_eq!(edges(a), edges(b));
+    }
+
+    #[test]
+    fn round_trip() {
+        for seed in 0..50 {
+            let graph = random_graph(seed, GraphKind::Directed, false);
+            assert_same(&graph, &ASimpleGraph::from_dot(&graph.to_dot()).unwrap());
+            assert_same(
+                &graph,
+                &ASimpleGraph::from_edge_list(&graph.to_edge_list()).unwrap(),
+            );
+            assert_same(&graph, &ASimpleGraph::from_json(&graph.to_json()).unwrap());
+
+            for (kind, multigraph) in [
+                (GraphKind::Undirected, false),
+                (GraphKind::Directed, true),
+                (GraphKind::Undirected, true),
+            ] {
+                let graph = random_graph(seed, kind, multigraph);
+                assert_same(&graph, &ASimpleGraph::from_dot(&graph.to_dot()).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn dot() {
+        let input = "strict digraph G {\n  rankdir=LR // comment\n  node [shape=box];\n  a -> b -> c [weight=3, color=red]\n  /* block\n comment */ d\n}\n";
+        let graph: ASimpleGraph<String> = ASimpleGraph::from_dot(input).unwrap();
+        assert_eq!(graph.nodes, vec!["a", "b", "c", "d"]);
+        assert_eq!(
+            graph.shortest_path("a".to_string(), "c".to_string()),
+            Some((6, vec!["a".to_string(), "b".to_string(), "c".to_string()]))
+        );
+
+        let graph: ASimpleGraph<i32> = ASimpleGraph::from_dot("graph { 1--2; -3 -- 1 }").unwrap();
+        assert_eq!(graph.kind(), GraphKind::Undirected);
+        assert_eq!(graph.shortest_path(2, -3), Some((2, vec![2, 1, -3])));
+
+        //Parallel edges survive as long as their labels differ
+        let graph: ASimpleGraph<String> =
+            ASimpleGraph::from_dot("digraph { a -> b [label=x]; a -> b [label=y, weight=2] }")
+                .unwrap();
+        assert!(graph.is_multigraph());
+        assert_eq!(graph.adjacency_list[0].len(), 2);
+        let graph: ASimpleGraph<String> =
+            ASimpleGraph::from_dot("strict digraph { a -> b [label=x]; a -> b [label=y] }")
+                .unwrap();
+        assert_eq!(graph.adjacency_list[0].len(), 1);
+
+        let graph: ASimpleGraph<String> = ASimpleGraph::from_dot("digraph{x->y}").unwrap();
+        assert_eq!(graph.nodes, vec!["x", "y"]);
+
+        let err = ASimpleGraph::<String>::from_dot("digraph {\n  a -> \n}").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        let err = ASimpleGraph::<String>::from_dot("digraph {\n  a -- b\n}").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        let err = ASimpleGraph::<u32>::from_dot("digraph { 1 -> x }").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError {
+                line: 1,
+                column: 16,
+                message: "invalid node key \"x\"".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn edge_list() {
+        let graph: ASimpleGraph<String> =
+            ASimpleGraph::from_edge_list("a,b,1\r\nb,c,2\nd,,\n").unwrap();
+        assert_eq!(graph.nodes, vec!["a", "b", "c", "d"]);
+        assert_eq!(graph.adjacency_list[1][0].weight, 2);
+
+        let err =
+            ASimpleGraph::<String>::from_edge_list("from,to,weight\na,b,1\na,b,x\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 5));
+        let err = ASimpleGraph::<String>::from_edge_list("a,b\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+        let err = ASimpleGraph::<String>::from_edge_list("a,\"b,1\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 3));
+    }
+
+    #[test]
+    fn json() {
+        let graph: ASimpleGraph<String> =
+            ASimpleGraph::from_json(r#"{"a": [{"weight": 5, "to": "bü"}], "bü": []}"#).unwrap();
+        assert_eq!(graph.nodes, vec!["a", "bü"]);
+        assert_eq!(graph.adjacency_list[0][0].weight, 5);
+
+        let err =
+            ASimpleGraph::<String>::from_json("{\n  \"a\": [{\"to\": \"z\", \"weight\": 1}]\n}")
+                .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 16));
+        let err = ASimpleGraph::<String>::from_json("{\"a\": [}").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 8));
+        assert_eq!(err.to_string(), "1:8: expected '{', found '}'");
+    }
+}
@@ -0,0 +1,302 @@
+//Point to point path queries that go further than shortest_path:
+//  Yen's algorithm for the k cheapest loopless paths
+//  Dijkstra run from both ends at once
+//Both work on indices, the GraphView methods turn them back into keys
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::hash::Hash;
+
+use super::{GraphKind, GraphView};
+
+//Cheapest edge from u straight to v. There can be more than one in a multigraph
+fn weight<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(graph: &G, u: usize, v: usize) -> u32 {
+    graph
+        .neighbors(u)
+        .filter(|&(to, _)| to == v)
+        .map(|(_, w)| w)
+        .min()
+        .unwrap()
+}
+
+//Walks a parent array back from `to`, the result still has to be reversed
+fn trace(parent: &[Option<usize>], from: usize, to: usize) -> Vec<usize> {
+    let mut path = vec![to];
+    let mut p = to;
+    while p != from {
+        p = parent[p].unwrap();
+        path.push(p);
+    }
+    path
+}
+
+//Plain Dijkstra from src to dst that can't use the blocked nodes, nor the blocked
+//edges (which block every parallel edge between the same two nodes)
+fn restricted<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    src: usize,
+    dst: usize,
+    blocked_nodes: &[bool],
+    blocked_edges: &HashSet<(usize, usize)>,
+) -> Option<(u32, Vec<usize>)> {
+    let mut distance = vec![None; graph.node_count()];
+    let mut parent = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    distance[src] = Some(0);
+    heap.push(Reverse((0u32, src)));
+    while let Some(Reverse((d, u))) = heap.pop() {
+        if distance[u].is_some_and(|best| d > best) {
+            continue;
+        }
+        if u == dst {
+            let mut path = trace(&parent, src, dst);
+            path.reverse();
+            return Some((d, path));
+        }
+        for (node, w) in graph.neighbors(u) {
+            if blocked_nodes[node] || blocked_edges.contains(&(u, node)) {
+                continue;
+            }
+            let Some(through_u) = d.checked_add(w) else {
+                continue;
+            };
+            if distance[node].is_none_or(|old| through_u < old) {
+                distance[node] = Some(through_u);
+                parent[node] = Some(u);
+                heap.push(Reverse((through_u, node)));
+            }
+        }
+    }
+    None
+}
+
+//Yen's algorithm. Every path found so far is taken apart at each of its nodes (the
+//spur node): the part before it is kept (the root), and a shortest path from the
+//spur node to dst is looked for that doesn't reuse a node of the root nor leave
+//the spur node along an edge an earlier path with the same root already took.
+//The cheapest of all these candidates is the next path
+pub(super) fn k_shortest<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    src: usize,
+    dst: usize,
+    k: usize,
+) -> Vec<(u32, Vec<usize>)> {
+    let no_nodes = vec![false; graph.node_count()];
+    let mut found = match restricted(graph, src, dst, &no_nodes, &HashSet::new()) {
+        Some(first) if k > 0 => vec![first],
+        _ => return vec![],
+    };
+    let mut candidates = BinaryHeap::new();
+    let mut seen: HashSet<Vec<usize>> = found.iter().map(|(_, p)| p.clone()).collect();
+    while found.len() < k {
+        let last = found.last().unwrap().1.clone();
+        let mut blocked_nodes = no_nodes.clone();
+        let mut root_cost: u32 = 0;
+        for i in 0..last.len() - 1 {
+            let (root, spur) = (&last[..=i], last[i]);
+            let blocked_edges = found
+                .iter()
+                .filter(|(_, p)| p.len() > i + 1 && &p[..=i] == root)
+                .map(|(_, p)| (p[i], p[i + 1]))
+                .collect();
+            if let Some((cost, spur_path)) =
+                restricted(graph, spur, dst, &blocked_nodes, &blocked_edges)
+            {
+                let mut path = last[..i].to_vec();
+                path.extend(spur_path);
+                if let Some(total) = root_cost.checked_add(cost) {
+                    if seen.insert(path.clone()) {
+                        candidates.push(Reverse((total, path)));
+                    }
+                }
+            }
+            blocked_nodes[spur] = true;
+            root_cost += weight(graph, spur, last[i + 1]);
+        }
+        match candidates.pop() {
+            Some(Reverse(next)) => found.push(next),
+            None => break,
+        }
+    }
+    found
+}
+
+//Two searches, one forward from src and one backward from dst, take turns settling
+//whichever of them has the closer node. Every edge that links a node seen by one to
+//a node seen by the other is a candidate path. Once the two closest unsettled nodes
+//together are no closer than the best candidate, nothing can beat it.
+//The balls around both ends are usually much smaller than one ball reaching all the
+//way from src to dst.
+//Directed graphs don't keep incoming edges, so the backward search needs them
+//collected first
+pub(super) fn bidirectional<K: Clone + Eq + Hash, G: GraphView<K> + ?Sized>(
+    graph: &G,
+    src: usize,
+    dst: usize,
+) -> Option<(u32, Vec<usize>)> {
+    let n = graph.node_count();
+    let incoming = match graph.kind() {
+        GraphKind::Undirected => None,
+        GraphKind::Directed => {
+            let mut incoming: Vec<Vec<(usize, u32)>> = vec![vec![]; n];
+            for from in 0..n {
+                for (to, w) in graph.neighbors(from) {
+                    incoming[to].push((from, w));
+                }
+            }
+            Some(incoming)
+        }
+    };
+    //Index 0 is the forward search, 1 the backward one
+    let mut distance = [vec![None; n], vec![None; n]];
+    let mut parent = [vec![None; n], vec![None; n]];
+    let mut heap = [BinaryHeap::new(), BinaryHeap::new()];
+    distance[0][src] = Some(0u32);
+    distance[1][dst] = Some(0u32);
+    heap[0].push(Reverse((0u32, src)));
+    heap[1].push(Reverse((0u32, dst)));
+    //Best (cost, node where both searches meet) so far
+    let mut best = if src == dst { Some((0, src)) } else { None };
+    while let (Some(&Reverse((top0, _))), Some(&Reverse((top1, _)))) =
+        (heap[0].peek(), heap[1].peek())
+    {
+        if best.is_some_and(|(cost, _)| top0 as u64 + top1 as u64 >= cost as u64) {
+            break;
+        }
+        let side = if top0 <= top1 { 0 } else { 1 };
+        let Reverse((d, u)) = heap[side].pop().unwrap();
+        if distance[side][u].is_some_and(|settled| d > settled) {
+            continue;
+        }
+        let edges: Vec<(usize, u32)> = match (side, &incoming) {
+            (1, Some(incoming)) => incoming[u].clone(),
+            _ => graph.neighbors(u).collect(),
+        };
+        for (node, w) in edges {
+            let Some(through_u) = d.checked_add(w) else {
+                continue;
+            };
+            if distance[side][node].is_none_or(|old| through_u < old) {
+                distance[side][node] = Some(through_u);
+                parent[side][node] = Some(u);
+                heap[side].push(Reverse((through_u, node)));
+                let total = distance[1 - side][node].and_then(|other| through_u.checked_add(other));
+                if let Some(total) = total {
+                    if best.is_none_or(|(cost, _)| total < cost) {
+                        best = Some((total, node));
+                    }
+                }
+            }
+        }
+    }
+    let (cost, meet) = best?;
+    let mut path = trace(&parent[0], src, meet);
+    path.reverse();
+    path.pop();
+    path.extend(trace(&parent[1], dst, meet));
+    Some((cost, path))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::generate::random_graph;
+    use super::super::{ASimpleGraph, GraphKind};
+
+    //The example from the Wikipedia article on Yen's algorithm
+    fn yen() -> ASimpleGraph<char> {
+        let mut graph = ASimpleGraph::new();
+        for &(from, to, weight) in &[
+            ('C', 'D', 3),
+            ('C', 'E', 2),
+            ('D', 'F', 4),
+            ('E', 'D', 1),
+            ('E', 'F', 2),
+            ('E', 'G', 3),
+            ('F', 'G', 2),
+            ('F', 'H', 1),
+            ('G', 'H', 2),
+        ] {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    #[test]
+    fn k_shortest() {
+        let graph = yen();
+        let paths = graph.k_shortest_paths('C', 'H', 3);
+        assert_eq!(paths[0], (5, vec!['C', 'E', 'F', 'H']));
+        assert_eq!(paths[1], (7, vec!['C', 'E', 'G', 'H']));
+        //Two paths cost 8, either one will do
+        assert_eq!(paths[2].0, 8);
+
+        //There are only 7 loopless paths, and each comes up once
+        let paths = graph.k_shortest_paths('C', 'H', 100);
+        assert_eq!(paths.len(), 7);
+        for pair in paths.windows(2) {
+            assert!(pair[0].0 <= pair[1].0);
+            assert_ne!(pair[0].1, pair[1].1);
+        }
+
+        assert_eq!(graph.k_shortest_paths('H', 'C', 3), vec![]);
+        assert_eq!(graph.k_shortest_paths('C', 'H', 0), vec![]);
+        assert_eq!(graph.k_shortest_paths('C', 'C', 3), vec![(0, vec!['C'])]);
+    }
+
+    #[test]
+    fn k_shortest_random() {
+        for seed in 0..10 {
+            let graph = random_graph(20, 60, GraphKind::Directed, seed);
+            let paths = graph.k_shortest_paths(0, 1, 10);
+            assert_eq!(paths.first().cloned(), graph.shortest_path(0, 1));
+            for (cost, path) in &paths {
+                //Costs add up and no node shows up twice
+                let sum: u32 = path
+                    .windows(2)
+                    .map(|e| {
+                        graph
+                            .edges(e[0])
+                            .unwrap()
+                            .iter()
+                            .find(|x| x.1 == e[1])
+                            .unwrap()
+                            .0
+                    })
+                    .sum();
+                assert_eq!(*cost, sum);
+                let mut nodes = path.clone();
+                nodes.sort();
+                nodes.dedup();
+                assert_eq!(nodes.len(), path.len());
+            }
+        }
+    }
+
+    #[test]
+    fn bidirectional() {
+        let graph = yen();
+        assert_eq!(
+            graph.bidirectional_shortest_path('C', 'H'),
+            Some((5, vec!['C', 'E', 'F', 'H']))
+        );
+        assert_eq!(graph.bidirectional_shortest_path('H', 'C'), None);
+        assert_eq!(
+            graph.bidirectional_shortest_path('D', 'D'),
+            Some((0, vec!['D']))
+        );
+        assert_eq!(graph.bidirectional_shortest_path('C', 'X'), None);
+
+        for seed in 0..20 {
+            for kind in [GraphKind::Directed, GraphKind::Undirected] {
+                let graph = random_graph(50, 120, kind, seed);
+                for (from, to) in [(0, 1), (2, 49), (7, 7), (13, 30)] {
+                    let fast = graph.bidirectional_shortest_path(from, to);
+                    let slow = graph.shortest_path(from, to);
+                    assert_eq!(fast.as_ref().map(|p| p.0), slow.as_ref().map(|p| p.0));
+                    if let Some((_, path)) = fast {
+                        assert_eq!((path[0], path[path.len() - 1]), (from, to));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//Precomputed answers to "is there a path from a to b". Nodes of a strongly connected
+//component all reach the same nodes, so everything works on the condensation, a DAG
+//whose nodes are the components in topological order (every edge goes to a higher
+//index). Two ways to label it:
+//  Closure: a bitset per component with a bit for every component it reaches, one
+//    AND per query. Built back to front, each row is its own bit ORed with the rows
+//    of its successors. C^2 / 8 bytes, which stops being an option around 100k components
+//  Intervals (GRAIL, Yildirim et al.): k DFS runs over the DAG with random child order
+//    give every component its post order rank and the lowest rank below it. If a
+//    reaches b, the range of b lies inside the range of a for every run, so one run
+//    where it doesn't rules the path out. The tree of the first run proves paths the
+//    other way around: a tree descendant is always reachable. Anything the labels can't
+//    settle falls back to a DFS that skips every component the labels rule out.
+//    O(k * C + E) memory, the DAG is kept for the fallback search
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
+
+use super::{scc_indices, GraphView};
+use crate::rng::Rng;
+
+//More runs rule out more pairs, but each one costs a pass over the DAG and
+//8 bytes per component
+const MAX_TRAVERSALS: usize = 5;
+
+pub struct Reachability<K> {
+    component: HashMap<K, usize>,
+    labels: Labels,
+}
+
+enum Labels {
+    Closure {
+        words: usize,
+        bits: Vec<u64>,
+    },
+    Intervals {
+        dag: Vec<Vec<usize>>,
+        //(pre, post) order of the first run, for the tree descendant check
+        tree: Vec<(u32, u32)>,
+        //(lowest rank below, rank) of component c in run t at c * traversals + t
+        ranges: Vec<(u32, u32)>,
+        traversals: usize,
+    },
+}
+
+impl<K: Clone + Eq + Hash> Reachability<K> {
+    //Closure if it fits into max_bytes (or there's no limit), intervals otherwise with
+    //as many runs as fit, but at least one
+    pub(super) fn new<G: GraphView<K> + ?Sized>(graph: &G, max_bytes: Option<usize>) -> Self {
+        let components = scc_indices(graph);
+        let c = components.len();
+        let mut component_of = vec![0; graph.node_count()];
+        let mut component = HashMap::with_capacity(graph.node_count());
+        for (i, members) in components.iter().enumerate() {
+            for &n in members {
+                component_of[n] = i;
+                component.insert(graph.node(n).clone(), i);
+            }
+        }
+        let mut dag: Vec<Vec<usize>> = vec![vec![]; c];
+        for from in 0..graph.node_count() {
+            for (to, _) in graph.neighbors(from) {
+                if component_of[from] != component_of[to] {
+                    dag[component_of[from]].push(component_of[to]);
+                }
+            }
+        }
+        for edges in &mut dag {
+            edges.sort_unstable();
+            edges.dedup();
+            edges.shrink_to_fit();
+        }
+
+        let words = c.div_ceil(64);
+        let labels = match max_bytes {
+            Some(max) if c * words * 8 > max => {
+                //The tree and the DAG are needed anyway, runs get what is left
+                let fixed = c * 8 + dag_bytes(&dag);
+                let traversals =
+                    (max.saturating_sub(fixed) / (8 * c.max(1))).clamp(1, MAX_TRAVERSALS);
+                intervals(dag, traversals)
+            }
+            _ => closure(&dag, words),
+        };
+        Reachability { component, labels }
+    }
+
+    //Every node reaches itself. Nodes that aren't in the graph reach nothing
+    pub fn reaches(&self, from: &K, to: &K) -> bool {
+        match (self.component.get(from), self.component.get(to)) {
+            (Some(&u), Some(&v)) => u == v || self.labels.reaches(u, v),
+            _ => false,
+        }
+    }
+
+    //False if the index had to fall back to intervals to stay under its memory limit
+    pub fn is_closure(&self) -> bool {
+        matches!(self.labels, Labels::Closure { .. })
+    }
+
+    //Size of the labels, the key lookup table comes on top
+    pub fn label_bytes(&self) -> usize {
+        match &self.labels {
+            Labels::Closure { bits, .. } => bits.len() * 8,
+            Labels::Intervals {
+                dag, tree, ranges, ..
+            } => (tree.len() + ranges.len()) * 8 + dag_bytes(dag),
+        }
+    }
+}
+
+fn dag_bytes(dag: &[Vec<usize>]) -> usize {
+    dag.iter()
+        .map(|edges| mem::size_of::<Vec<usize>>() + edges.len() * mem::size_of::<usize>())
+        .sum()
+}
+
+fn closure(dag: &[Vec<usize>], words: usize) -> Labels {
+    let mut bits = vec![0u64; dag.len() * words];
+    for c in (0..dag.len()).rev() {
+        bits[c * words + c / 64] |= 1 << (c % 64);
+        for &s in &dag[c] {
+            //s > c, so row s sits in the upper half of the split
+            let (low, high) = bits.split_at_mut(s * words);
+            let row = &mut low[c * words..(c + 1) * words];
+            for (word, other) in row.iter_mut().zip(&high[..words]) {
+                *word |= other;
+            }
+        }
+    }
+    Labels::Closure { words, bits }
+}
+
+fn intervals(dag: Vec<Vec<usize>>, traversals: usize) -> Labels {
+    let c = dag.len();
+    let mut tree = vec![(0, 0); c];
+    let mut ranges = vec![(0, 0); c * traversals];
+    let mut rng = Rng::new(c as u64);
+    for t in 0..traversals {
+        //The first run goes in plain order, the others shuffle roots and children
+        let mut order = dag.clone();
+        let mut roots: Vec<usize> = (0..c).collect();
+        if t > 0 {
+            rng.shuffle(&mut roots);
+            for edges in &mut order {
+                rng.shuffle(edges);
+            }
+        }
+        let mut visited = vec![false; c];
+        let mut rank = vec![0u32; c];
+        let (mut pre, mut post) = (0, 0);
+        for root in roots {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            if t == 0 {
+                tree[root].0 = pre;
+                pre += 1;
+            }
+            let mut call = vec![(root, 0)];
+            while let Some((u, i)) = call.last_mut() {
+                let u = *u;
+                if let Some(&v) = order[u].get(*i) {
+                    *i += 1;
+                    if !visited[v] {
+                        visited[v] = true;
+                        if t == 0 {
+                            tree[v].0 = pre;
+                            pre += 1;
+                        }
+                        call.push((v, 0));
+                    }
+                } else {
+                    call.pop();
+                    rank[u] = post;
+                    if t == 0 {
+                        tree[u].1 = post;
+                    }
+                    post += 1;
+                }
+            }
+        }
+        //Successors have higher indices, so they are done before their predecessors
+        for u in (0..c).rev() {
+            let low = dag[u]
+                .iter()
+                .map(|&v| ranges[v * traversals + t].0)
+                .fold(rank[u], u32::min);
+            ranges[u * traversals + t] = (low, rank[u]);
+        }
+    }
+    Labels::Intervals {
+        dag,
+        tree,
+        ranges,
+        traversals,
+    }
+}
+
+impl Labels {
+    fn reaches(&self, u: usize, v: usize) -> bool {
+        match self {
+            Labels::Closure { words, bits } => bits[u * words + v / 64] >> (v % 64) & 1 == 1,
+            Labels::Intervals {
+                dag,
+                tree,
+                ranges,
+                traversals,
+            } => {
+                let descendant = |a: usize| tree[a].0 <= tree[v].0 && tree[v].1 <= tree[a].1;
+                let inside = |a: usize| {
+                    (0..*traversals).all(|t| {
+                        let (outer, inner) =
+                            (ranges[a * traversals + t], ranges[v * traversals + t]);
+                        outer.0 <= inner.0 && inner.1 <= outer.1
+                    })
+                };
+                if descendant(u) {
+                    return true;
+                }
+                if !inside(u) {
+                    return false;
+                }
+                let mut seen = HashSet::new();
+                let mut stack = vec![u];
+                while let Some(a) = stack.pop() {
+                    for &b in &dag[a] {
+                        if b == v || descendant(b) {
+                            return true;
+                        }
+                        if inside(b) && seen.insert(b) {
+                            stack.push(b);
+                        }
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::generate::{erdos_renyi, path, random_dag};
+    use super::super::{ASimpleGraph, GraphKind, GraphView};
+    use crate::rng::Rng;
+
+    //Plain BFS from every node
+    fn closure(graph: &ASimpleGraph<usize>) -> Vec<Vec<bool>> {
+        let n = graph.node_count();
+        (0..n)
+            .map(|from| {
+                let mut seen = vec![false; n];
+                seen[from] = true;
+                let mut stack = vec![from];
+                while let Some(u) = stack.pop() {
+                    for (v, _) in graph.neighbors(u) {
+                        if !seen[v] {
+                            seen[v] = true;
+                            stack.push(v);
+                        }
+                    }
+                }
+                seen
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_as_search() {
+        //Sparse enough that most components are single nodes, so the closure
+        //outgrows the DAG and there is room for several runs below it
+        let n = 1000;
+        let mut graphs = vec![];
+        for seed in 0..2 {
+            let mut rng = Rng::new(seed);
+            graphs.push(erdos_renyi(n, 0.001, GraphKind::Directed, &mut rng));
+            graphs.push(erdos_renyi(n, 0.0015, GraphKind::Directed, &mut rng));
+            graphs.push(random_dag(n, 0.002, &mut rng));
+        }
+        for graph in &graphs {
+            let expected = closure(graph);
+            let full = graph.reachability();
+            assert!(full.is_closure());
+            //No room for the closure, so a single interval run
+            let small = graph.reachability_within(0);
+            assert!(!small.is_closure());
+            //Just short of the closure, which leaves room for more runs
+            let budget = full.label_bytes() - 1;
+            let some = graph.reachability_within(budget);
+            assert!(!some.is_closure());
+            assert!(some.label_bytes() > small.label_bytes());
+            assert!(some.label_bytes() <= budget);
+            //Every fifth source against every target keeps this quick in debug builds
+            for (a, row) in expected.iter().enumerate().step_by(5) {
+                for (b, &reached) in row.iter().enumerate() {
+                    assert_eq!(full.reaches(&a, &b), reached);
+                    assert_eq!(small.reaches(&a, &b), reached);
+                    assert_eq!(some.reaches(&a, &b), reached);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let mut graph = ASimpleGraph::new();
+        for (from, to) in [("a", "b"), ("b", "a"), ("b", "c"), ("d", "c")] {
+            graph.add_edge(from, to, 1);
+        }
+        let index = graph.reachability();
+        assert!(index.reaches(&"a", &"a"));
+        assert!(index.reaches(&"b", &"a"));
+        assert!(index.reaches(&"a", &"c"));
+        assert!(!index.reaches(&"c", &"a"));
+        assert!(!index.reaches(&"a", &"d"));
+        assert!(!index.reaches(&"a", &"x"));
+    }
+
+    #[test]
+    fn deep() {
+        //The closure would take 5GB here
+        let n = 200_000;
+        let graph = path(n, GraphKind::Directed);
+        let index = graph.reachability_within(1 << 20);
+        assert!(!index.is_closure());
+        assert!(index.reaches(&0, &(n - 1)));
+        assert!(!index.reaches(&(n - 1), &0));
+        assert!(index.reaches(&1000, &1001));
+    }
+}
@@ -0,0 +1,293 @@
+//Undo/redo history on top of third::List
+//Every state sits in a list whose tail is the state it was committed on top of, so
+//committing is an append and undo is a tail, and all versions share their common past.
+//Undoing and then committing something else doesn't lose anything: the old future
+//stays around as another branch, which makes the whole thing a tree (an undo tree).
+//Every state that was built on keeps its children, each with the last time it was
+//entered, and redo goes to the most recent one. That is also the branch that was
+//visited last, since nothing below a child can be reached without entering it first.
+//States are kept behind an Rc, nothing ever gets cloned, S doesn't even need Clone.
+//The children are found by the address of that Rc, which can't be reused while the
+//children (whose tails hold it) are still around.
+//With a max depth, states further back than that can't be undone to anymore. Once
+//as many have piled up as are kept, they are cut off for real: the tree gets rebuilt
+//from the oldest state still in reach (new cells pointing at the same states), and
+//branches that only meet the current one in the cut off part go with them. A state
+//lives through a few of those rebuilds at most, so commit stays O(1) amortized
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::third::List;
+
+//Children of one state, each with the time it was last entered
+type Children<S> = Vec<(List<Rc<S>>, u64)>;
+
+pub struct History<S> {
+    current: List<Rc<S>>,
+    //Length of current, counting the states below base
+    len: usize,
+    //Keyed by the address of the parent state
+    children: HashMap<*const S, Children<S>>,
+    clock: u64,
+    max_depth: Option<usize>,
+    //How many states at the bottom of every list are already out of reach
+    base: usize,
+}
+
+impl<S> History<S> {
+    pub fn new(initial: S) -> Self {
+        History {
+            current: List::new().append(Rc::new(initial)),
+            len: 1,
+            children: HashMap::new(),
+            clock: 0,
+            max_depth: None,
+            base: 0,
+        }
+    }
+
+    //Keeps at most max_depth states to undo to
+    pub fn with_max_depth(initial: S, max_depth: usize) -> Self {
+        History {
+            max_depth: Some(max_depth),
+            ..Self::new(initial)
+        }
+    }
+
+    pub fn current(&self) -> &S {
+        self.current.head().unwrap()
+    }
+
+    //How many times undo works from here
+    pub fn depth(&self) -> usize {
+        self.len - 1 - self.base
+    }
+
+    fn key(list: &List<Rc<S>>) -> *const S {
+        Rc::as_ptr(list.head().unwrap())
+    }
+
+    pub fn commit(&mut self, state: S) {
+        let child = self.current.append(Rc::new(state));
+        self.clock += 1;
+        self.children
+            .entry(Self::key(&self.current))
+            .or_default()
+            .push((child.clone(), self.clock));
+        self.current = child;
+        self.len += 1;
+        if let Some(max) = self.max_depth {
+            if self.depth() > max {
+                self.base = self.len - 1 - max;
+                if self.base > max {
+                    self.prune();
+                }
+            }
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<&S> {
+        if self.depth() == 0 {
+            return None;
+        }
+        self.current = self.current.tail();
+        self.len -= 1;
+        Some(self.current())
+    }
+
+    //Children of the current state with their positions, most recently entered first
+    fn children(&self) -> Vec<(usize, &List<Rc<S>>)> {
+        let children = match self.children.get(&Self::key(&self.current)) {
+            Some(children) => children,
+            None => return vec![],
+        };
+        let mut order: Vec<_> = children.iter().enumerate().collect();
+        order.sort_unstable_by_key(|(_, (_, entered))| Reverse(*entered));
+        order
+            .into_iter()
+            .map(|(i, (child, _))| (i, child))
+            .collect()
+    }
+
+    //States redo_branch can go to, redo takes the first one
+    pub fn branches(&self) -> Vec<&S> {
+        self.children()
+            .into_iter()
+            .map(|(_, child)| &**child.head().unwrap())
+            .collect()
+    }
+
+    pub fn redo(&mut self) -> Option<&S> {
+        self.redo_branch(0)
+    }
+
+    pub fn redo_branch(&mut self, branch: usize) -> Option<&S> {
+        let (i, _) = *self.children().get(branch)?;
+        self.clock += 1;
+        let children = self.children.get_mut(&Self::key(&self.current)).unwrap();
+        children[i].1 = self.clock;
+        self.current = children[i].0.clone();
+        self.len += 1;
+        Some(self.current())
+    }
+
+    //Rebuilds the tree below the lowest reachable state (the root) without the base
+    //states under it. The keys stay the same, they are the states and not the cells.
+    //Anything that isn't below the root is dropped with the old map
+    fn prune(&mut self) {
+        let root = Rc::clone(self.current.nth(self.len - self.base - 1).unwrap());
+        let target = Self::key(&self.current);
+        let mut old = std::mem::take(&mut self.children);
+        let mut stack = vec![List::new().append(root)];
+        while let Some(list) = stack.pop() {
+            let key = Self::key(&list);
+            if let Some(children) = old.remove(&key) {
+                let copies = children
+                    .into_iter()
+                    .map(|(child, stamp)| {
+                        let copy = list.append(Rc::clone(child.head().unwrap()));
+                        stack.push(copy.clone());
+                        (copy, stamp)
+                    })
+                    .collect();
+                self.children.insert(key, copies);
+            }
+            if key == target {
+                self.current = list;
+            }
+        }
+        self.len -= self.base;
+        self.base = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::History;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn basics() {
+        let mut history = History::new("a");
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), None);
+
+        history.commit("b");
+        history.commit("c");
+        assert_eq!(*history.current(), "c");
+        assert_eq!(history.depth(), 2);
+
+        assert_eq!(history.undo(), Some(&"b"));
+        assert_eq!(history.undo(), Some(&"a"));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(&"b"));
+        assert_eq!(history.redo(), Some(&"c"));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn branching() {
+        let mut history = History::new(0);
+        history.commit(1);
+        history.commit(2);
+        history.undo();
+        //A second branch after 1, the 2 is still there
+        history.commit(3);
+        history.commit(4);
+        history.undo();
+        history.undo();
+        assert_eq!(*history.current(), 1);
+        assert_eq!(history.branches(), vec![&3, &2]);
+
+        //Redo goes back the way it came
+        assert_eq!(history.redo(), Some(&3));
+        assert_eq!(history.redo(), Some(&4));
+        history.undo();
+        history.undo();
+        assert_eq!(history.redo_branch(1), Some(&2));
+        assert_eq!(history.redo(), None);
+        //Now 2 is the most recent one
+        history.undo();
+        assert_eq!(history.branches(), vec![&2, &3]);
+        assert_eq!(history.redo_branch(2), None);
+    }
+
+    //Counts how many are still alive
+    struct Tracked(usize, Rc<Cell<usize>>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() - 1);
+        }
+    }
+
+    #[test]
+    fn max_depth() {
+        let alive = Rc::new(Cell::new(0));
+        let state = |n| {
+            alive.set(alive.get() + 1);
+            Tracked(n, Rc::clone(&alive))
+        };
+        let mut history = History::with_max_depth(state(0), 3);
+        for n in 1..=100 {
+            history.commit(state(n));
+        }
+        assert_eq!(history.depth(), 3);
+        //Never more than twice the depth hangs around
+        assert!(alive.get() <= 2 * 3 + 2);
+        for n in (97..100).rev() {
+            assert_eq!(history.undo().map(|s| s.0), Some(n));
+        }
+        assert!(history.undo().is_none());
+
+        //A new branch off the oldest state, the limit holds there too
+        history.commit(state(1000));
+        for n in 1001..1005 {
+            history.commit(state(n));
+        }
+        assert_eq!(history.current().0, 1004);
+        for _ in 0..3 {
+            history.undo();
+        }
+        assert!(history.undo().is_none());
+        assert_eq!(history.current().0, 1001);
+        drop(history);
+        assert_eq!(alive.get(), 0);
+    }
+
+    #[test]
+    fn many_branches() {
+        //Lots of leaves must not slow down commit or redo
+        let mut history = History::new(0);
+        for n in 1..100_000 {
+            history.commit(n);
+            history.undo();
+        }
+        assert_eq!(history.branches().len(), 99_999);
+        assert_eq!(history.redo(), Some(&99_999));
+        history.undo();
+        assert_eq!(history.redo_branch(99_998), Some(&1));
+        history.undo();
+        assert_eq!(history.branches()[..2], [&1, &99_999]);
+    }
+
+    #[test]
+    fn long() {
+        let mut history = History::with_max_depth(0, 1000);
+        for n in 1..200_000 {
+            history.commit(n);
+            if n % 7 == 0 {
+                history.undo();
+            }
+        }
+        assert_eq!(history.depth(), 1000);
+        let mut unlimited = History::new(0);
+        for n in 1..200_000 {
+            unlimited.commit(n);
+        }
+        assert_eq!(unlimited.depth(), 199_999);
+    }
+}
@@ -1,12 +1,23 @@
 pub mod first;
 pub mod fourth;
+pub mod history;
+pub mod persistent_map;
+pub mod persistent_queue;
 pub mod queue;
+pub mod rng;
 pub mod second;
+pub mod skew_list;
+pub mod stream;
 pub mod third;
 pub mod MergeSort;
-//pub mod SimpleGraph; 
+pub mod SimpleGraph;
 pub mod QuickSort;
 pub mod unsafe_queue;
+pub mod union_find;
+pub mod zipper;
+
+pub use SimpleGraph::GraphView;
+
 fn main() {
     println!("Hello, world!");
 }
@@ -0,0 +1,227 @@
+//Persistent key/value map on a third::List<(K, V)>, an association list
+//insert just puts the pair in front, the newest entry for a key shadows the older
+//ones further down. get and the iterator take the first entry of every key they
+//come across, so they never see a shadowed one.
+//Every write returns a new version that shares the list with the old one:
+//  insert: O(1), shares everything
+//  remove: throws out every entry of the key, the list after the last of them is shared
+//  compact: throws out shadowed entries, same sharing as remove
+//Shadowed entries pile up with every overwrite, so insert compacts on its own once
+//the list has grown to twice what it was after the last compaction. That keeps the
+//list within a constant factor of the live entries, at O(1) amortized as long as
+//every version gets written to at most once. The compaction is strict, so a version
+//one insert away from compacting pays the full O(n) on every insert made from it.
+//Code that keeps inserting into one old version should compact that version first
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::third::{self, List, RcPointer};
+
+//Compacting tiny lists isn't worth it
+const MIN_COMPACT: usize = 16;
+
+pub struct PersistentMap<K, V> {
+    entries: List<(K, V)>,
+    //Length of entries, and the length at which insert compacts
+    len: usize,
+    compact_at: usize,
+}
+
+pub struct Iter<'a, K, V> {
+    entries: third::Iter<'a, (K, V), RcPointer>,
+    seen: HashSet<&'a K>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> PersistentMap<K, V> {
+    pub fn new() -> Self {
+        PersistentMap {
+            entries: List::new(),
+            len: 0,
+            compact_at: MIN_COMPACT,
+        }
+    }
+
+    fn with_entries(entries: List<(K, V)>, compact_at: usize) -> Self {
+        PersistentMap {
+            len: entries.len(),
+            entries,
+            compact_at,
+        }
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let map = PersistentMap {
+            entries: self.entries.append((key, value)),
+            len: self.len + 1,
+            compact_at: self.compact_at,
+        };
+        if map.len >= map.compact_at {
+            map.compact()
+        } else {
+            map
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    //Nothing gets copied if the key isn't there
+    pub fn remove(&self, key: &K) -> Self {
+        let entries = self.entries.filter(|(k, _)| k != key);
+        Self::with_entries(entries, self.compact_at)
+    }
+
+    //Same map without the shadowed entries
+    pub fn compact(&self) -> Self {
+        let mut seen = HashSet::new();
+        let entries = self.entries.filter(|(k, _)| seen.insert(k.clone()));
+        let len = entries.len();
+        PersistentMap {
+            entries,
+            len,
+            compact_at: (2 * len).max(MIN_COMPACT),
+        }
+    }
+
+    //Live entries, newest first
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            entries: self.entries.iter(),
+            seen: HashSet::new(),
+        }
+    }
+
+    //Number of live keys, O(n)
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    //Entries in the list, shadowed ones included
+    pub fn entries(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, K: Eq + Hash, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (k, v) in self.entries.by_ref() {
+            if self.seen.insert(k) {
+                return Some((k, v));
+            }
+        }
+        None
+    }
+}
+
+impl<K, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        PersistentMap {
+            entries: self.entries.clone(),
+            len: self.len,
+            compact_at: self.compact_at,
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Default for PersistentMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PersistentMap;
+    use std::collections::HashMap;
+
+    fn sorted(map: &PersistentMap<&'static str, i32>) -> Vec<(&'static str, i32)> {
+        let mut items: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn basics() {
+        let map = PersistentMap::new();
+        assert_eq!(map.get(&"a"), None);
+        assert!(map.is_empty());
+
+        let map = map.insert("a", 1).insert("b", 2).insert("a", 3);
+        assert_eq!(map.get(&"a"), Some(&3));
+        assert_eq!(map.get(&"b"), Some(&2));
+        assert!(map.contains_key(&"b"));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.entries(), 3);
+        assert_eq!(sorted(&map), vec![("a", 3), ("b", 2)]);
+
+        //Removing takes the shadowed entries along, nothing comes back
+        let removed = map.remove(&"a");
+        assert_eq!(removed.get(&"a"), None);
+        assert_eq!(removed.entries(), 1);
+        assert_eq!(sorted(&removed), vec![("b", 2)]);
+        assert!(removed.remove(&"b").is_empty());
+    }
+
+    #[test]
+    fn versions() {
+        let v1 = PersistentMap::new().insert("host", 1).insert("port", 80);
+        let v2 = v1.insert("port", 8080);
+        let v3 = v2.remove(&"host");
+        let v4 = v3.compact();
+        assert_eq!(sorted(&v1), vec![("host", 1), ("port", 80)]);
+        assert_eq!(sorted(&v2), vec![("host", 1), ("port", 8080)]);
+        assert_eq!(sorted(&v3), vec![("port", 8080)]);
+        assert_eq!(sorted(&v4), vec![("port", 8080)]);
+        assert_eq!((v3.entries(), v4.entries()), (2, 1));
+
+        //Writes share what they can with the version they came from
+        assert!(v2.entries.tail().ptr_eq(&v1.entries));
+        let v5 = v2.insert("user", 7).insert("mode", 2);
+        assert_eq!(v5.remove(&"user").entries.shared_suffix_len(&v2.entries), 3);
+        assert!(v2.remove(&"missing").entries.ptr_eq(&v2.entries));
+    }
+
+    #[test]
+    fn compaction() {
+        //Overwriting a few keys over and over doesn't grow the list
+        let mut map = PersistentMap::new();
+        let mut model = HashMap::new();
+        for i in 0..100_000 {
+            map = map.insert(i % 10, i);
+            model.insert(i % 10, i);
+            assert!(map.entries() <= 2 * 10 + super::MIN_COMPACT);
+        }
+        assert_eq!(map.len(), 10);
+        for (k, v) in &model {
+            assert_eq!(map.get(k), Some(v));
+        }
+        let mut live: Vec<(i32, i32)> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        live.sort();
+        let mut expected: Vec<(i32, i32)> = model.into_iter().collect();
+        expected.sort();
+        assert_eq!(live, expected);
+    }
+
+    #[test]
+    fn long() {
+        let mut map = PersistentMap::new();
+        for i in 0..200_000 {
+            map = map.insert(i, i * 2);
+        }
+        assert_eq!(map.len(), 200_000);
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.remove(&0).len(), 199_999);
+    }
+}
@@ -0,0 +1,249 @@
+//Persistent FIFO queue (Okasaki's banker's queue)
+//New elements are appended to back, a third::List kept in reverse, and taken off the
+//head of front, a lazy Stream. As soon as back gets longer than front, front becomes
+//front ++ reverse(back). Reversing costs O(n), but it only happens after n cheap
+//operations paid for it, so enqueue and dequeue are amortized O(1).
+//Every operation returns a new queue and leaves the old one alone. The reverse is
+//suspended inside front and only runs when a dequeue reaches it. Streams are
+//memoized, so every version sharing that front shares the result too: reusing a
+//version from right before a rotation doesn't pay for the rotation again.
+
+use crate::stream::Stream;
+use crate::third::List;
+
+pub struct Queue<T> {
+    front: Stream<T>,
+    front_len: usize,
+    back: List<T>,
+    back_len: usize,
+}
+
+impl<T: Clone + 'static> Queue<T> {
+    pub fn new() -> Self {
+        Queue {
+            front: Stream::empty(),
+            front_len: 0,
+            back: List::new(),
+            back_len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front_len + self.back_len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    //Keeps back no longer than front, which also means front is only ever empty
+    //when the whole queue is
+    fn balanced(front: Stream<T>, front_len: usize, back: List<T>, back_len: usize) -> Self {
+        if back_len <= front_len {
+            return Queue {
+                front,
+                front_len,
+                back,
+                back_len,
+            };
+        }
+        //back holds the newest element first, consing them on in that order leaves
+        //the oldest at the head
+        let reversed = Stream::lazy(move || {
+            let stream = back.iter().fold(Stream::empty(), |rest, elem| {
+                Stream::cons(elem.clone(), rest)
+            });
+            stream.head().cloned().map(|elem| (elem, stream.tail()))
+        });
+        Queue {
+            front: front.concat(&reversed),
+            front_len: front_len + back_len,
+            back: List::new(),
+            back_len: 0,
+        }
+    }
+
+    pub fn enqueue(&self, elem: T) -> Self {
+        Self::balanced(
+            self.front.clone(),
+            self.front_len,
+            self.back.append(elem),
+            self.back_len + 1,
+        )
+    }
+
+    //The queue without its first element. Like third::List::tail, an empty queue
+    //stays empty
+    pub fn dequeue(&self) -> Self {
+        if self.is_empty() {
+            return self.clone();
+        }
+        Self::balanced(
+            self.front.tail(),
+            self.front_len - 1,
+            self.back.clone(),
+            self.back_len,
+        )
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.front.head()
+    }
+}
+
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        Queue {
+            front: self.front.clone(),
+            front_len: self.front_len,
+            back: self.back.clone(),
+            back_len: self.back_len,
+        }
+    }
+}
+
+impl<T: Clone + 'static> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Queue;
+    use crate::rng::Rng;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    //Counts how often it gets cloned, which is how the queue copies elements around
+    struct Counted(Rc<Cell<usize>>);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            Counted(self.0.clone())
+        }
+    }
+
+    //Dequeues everything, returns it in order
+    fn drain(queue: &Queue<i32>) -> Vec<i32> {
+        let mut queue = queue.clone();
+        let mut out = vec![];
+        while let Some(&x) = queue.peek() {
+            out.push(x);
+            queue = queue.dequeue();
+        }
+        out
+    }
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+        assert_eq!(queue.peek(), None);
+        assert!(queue.dequeue().is_empty());
+
+        let queue = queue.enqueue(1).enqueue(2).enqueue(3);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.peek(), Some(&1));
+
+        let queue = queue.dequeue();
+        assert_eq!(queue.peek(), Some(&2));
+
+        let queue = queue.enqueue(4).enqueue(5);
+        assert_eq!(drain(&queue), vec![2, 3, 4, 5]);
+        assert!(queue.dequeue().dequeue().dequeue().dequeue().is_empty());
+    }
+
+    #[test]
+    fn snapshots() {
+        let empty = Queue::new();
+        let one = empty.enqueue(1);
+        let two = one.enqueue(2);
+        let three = two.enqueue(3);
+        //Two different futures of the same version
+        let left = two.dequeue().enqueue(10);
+        let right = two.enqueue(20).enqueue(21);
+
+        assert!(empty.is_empty());
+        assert_eq!(drain(&one), vec![1]);
+        assert_eq!(drain(&two), vec![1, 2]);
+        assert_eq!(drain(&three), vec![1, 2, 3]);
+        assert_eq!(drain(&left), vec![2, 10]);
+        assert_eq!(drain(&right), vec![1, 2, 20, 21]);
+
+        //Draining didn't change anything either
+        assert_eq!(three.len(), 3);
+        assert_eq!(three.peek(), Some(&1));
+    }
+
+    #[test]
+    fn snapshots_random() {
+        //Every version gets compared with a Vec model after all the others were made
+        let mut versions = vec![(Queue::new(), vec![])];
+        let mut rng = Rng::new(7);
+        for i in 0..2000 {
+            let pick = rng.below(versions.len());
+            let (queue, model): &(Queue<i32>, Vec<i32>) = &versions[pick];
+            let next = if rng.below(3) == 0 {
+                (queue.dequeue(), model.iter().skip(1).cloned().collect())
+            } else {
+                let mut model = model.clone();
+                model.push(i);
+                (queue.enqueue(i), model)
+            };
+            versions.push(next);
+        }
+        for (queue, model) in &versions {
+            assert_eq!(queue.len(), model.len());
+            assert_eq!(&drain(queue), model);
+        }
+    }
+
+    #[test]
+    fn reuse_before_rotation() {
+        let clones = Rc::new(Cell::new(0));
+        let mut queue = Queue::new();
+        while queue.front_len < 1000 || queue.back_len < queue.front_len {
+            queue = queue.enqueue(Counted(clones.clone()));
+        }
+        let n = queue.len();
+
+        //The next enqueue rotates, every one of these starts from the same version
+        clones.set(0);
+        for _ in 0..1000 {
+            let next = queue.enqueue(Counted(clones.clone()));
+            assert_eq!(next.back_len, 0);
+            assert!(next.peek().is_some());
+        }
+        assert!(clones.get() < 10_000, "{} clones", clones.get());
+
+        //Dequeue up to where the reversed part starts, the next peek forces it
+        let rotated = queue.enqueue(Counted(clones.clone()));
+        let mut at_reverse = rotated.clone();
+        for _ in 0..n / 2 {
+            at_reverse = at_reverse.dequeue();
+        }
+        clones.set(0);
+        for _ in 0..1000 {
+            assert!(at_reverse.peek().is_some());
+            let next = at_reverse.dequeue().enqueue(Counted(clones.clone()));
+            assert!(next.peek().is_some());
+        }
+        //One reverse of n / 2 + 1 elements, then a handful per iteration
+        assert!(clones.get() < n + 10_000, "{} clones", clones.get());
+        assert_eq!(rotated.len(), n + 1);
+    }
+
+    #[test]
+    fn long() {
+        let mut queue = Queue::new();
+        for i in 0..1_000_000 {
+            queue = queue.enqueue(i);
+        }
+        for i in 0..500_000 {
+            assert_eq!(queue.peek(), Some(&i));
+            queue = queue.dequeue();
+        }
+        assert_eq!(queue.len(), 500_000);
+    }
+}
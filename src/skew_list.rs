@@ -0,0 +1,348 @@
+//Skew binary random access list (Okasaki), a persistent list with O(log n) indexing
+//The elements sit in a short list (the spine) of complete binary trees, each with
+//2^k - 1 elements, read in preorder: root, left subtree, right subtree.
+//Tree sizes only ever grow along the spine, except that the first two may be equal.
+//  cons: if the first two trees have the same size, the new element becomes the root
+//    above both of them, otherwise it's a new tree of size 1. O(1) either way
+//  tail: the root of the first tree goes, its two subtrees take its place. O(1)
+//  get: skip whole trees until the right one, then walk down it. O(log n)
+//  update: like get, but copies the nodes on the way down and the spine in front of
+//    the tree, everything else is shared with the old version. O(log n)
+//The spine has O(log n) trees and they are O(log n) deep, so unlike third::List
+//dropping one can't run out of stack and needs no Drop of its own.
+//Pointers come from third::PointerKind, so there is an Rc and an Arc flavour
+
+use crate::third::{ArcPointer, PointerKind, RcPointer};
+
+pub type SkewList<T> = GenericSkewList<T, RcPointer>;
+pub type ArcSkewList<T> = GenericSkewList<T, ArcPointer>;
+
+pub struct GenericSkewList<T, P: PointerKind> {
+    head: Link<T, P>,
+}
+
+type Link<T, P> = Option<<P as PointerKind>::Pointer<Spine<T, P>>>;
+type TreeLink<T, P> = <P as PointerKind>::Pointer<Tree<T, P>>;
+
+struct Spine<T, P: PointerKind> {
+    size: usize,
+    tree: TreeLink<T, P>,
+    next: Link<T, P>,
+}
+
+struct Tree<T, P: PointerKind> {
+    elem: T,
+    //Both or neither
+    children: Option<(TreeLink<T, P>, TreeLink<T, P>)>,
+}
+
+pub struct Iter<'a, T, P: PointerKind> {
+    spine: Option<&'a Spine<T, P>>,
+    //Subtrees still to visit in the current tree, next one on top
+    trees: Vec<&'a Tree<T, P>>,
+}
+
+impl<T, P: PointerKind> GenericSkewList<T, P> {
+    pub fn new() -> Self {
+        GenericSkewList { head: None }
+    }
+
+    fn push(size: usize, tree: TreeLink<T, P>, next: Link<T, P>) -> Link<T, P> {
+        Some(P::new(Spine { size, tree, next }))
+    }
+
+    pub fn cons(&self, elem: T) -> Self {
+        if let Some(first) = &self.head {
+            if let Some(second) = &first.next {
+                if first.size == second.size {
+                    let tree = P::new(Tree {
+                        elem,
+                        children: Some((first.tree.clone(), second.tree.clone())),
+                    });
+                    let next = second.next.clone();
+                    return GenericSkewList {
+                        head: Self::push(2 * first.size + 1, tree, next),
+                    };
+                }
+            }
+        }
+        let tree = P::new(Tree {
+            elem,
+            children: None,
+        });
+        GenericSkewList {
+            head: Self::push(1, tree, self.head.clone()),
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|spine| &spine.tree.elem)
+    }
+
+    //Like third::List::tail, the tail of an empty list is empty
+    pub fn tail(&self) -> Self {
+        let head = match &self.head {
+            None => None,
+            Some(first) => match &first.tree.children {
+                None => first.next.clone(),
+                Some((left, right)) => {
+                    let half = first.size / 2;
+                    let rest = Self::push(half, right.clone(), first.next.clone());
+                    Self::push(half, left.clone(), rest)
+                }
+            },
+        };
+        GenericSkewList { head }
+    }
+
+    //Tree that holds element i, along with its size and the position of i in it
+    fn find(&self, mut i: usize) -> Option<(usize, &Tree<T, P>, usize)> {
+        let mut link = &self.head;
+        while let Some(spine) = link {
+            if i < spine.size {
+                return Some((spine.size, &spine.tree, i));
+            }
+            i -= spine.size;
+            link = &spine.next;
+        }
+        None
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        let (mut size, mut tree, mut i) = self.find(i)?;
+        //Position 0 is the root, then size / 2 elements on the left and as many on the right
+        while i > 0 {
+            let (left, right) = tree.children.as_ref().unwrap();
+            size /= 2;
+            if i <= size {
+                tree = left;
+                i -= 1;
+            } else {
+                tree = right;
+                i -= 1 + size;
+            }
+        }
+        Some(&tree.elem)
+    }
+
+    pub fn len(&self) -> usize {
+        let mut len = 0;
+        let mut link = &self.head;
+        while let Some(spine) = link {
+            len += spine.size;
+            link = &spine.next;
+        }
+        len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter {
+            spine: self.head.as_deref(),
+            trees: vec![],
+        }
+    }
+}
+
+impl<T: Clone, P: PointerKind> GenericSkewList<T, P> {
+    //New version with element i replaced, None if there is no element i
+    pub fn update(&self, i: usize, elem: T) -> Option<Self> {
+        //Spine entries in front of the tree that holds i get copied
+        let mut before = vec![];
+        let mut link = &self.head;
+        let mut i = i;
+        let spine = loop {
+            let spine = link.as_ref()?;
+            if i < spine.size {
+                break spine;
+            }
+            i -= spine.size;
+            before.push(spine);
+            link = &spine.next;
+        };
+
+        //Down the tree, remembering every node passed and which way it went
+        let (mut size, mut tree) = (spine.size, &spine.tree);
+        let mut path = vec![];
+        while i > 0 {
+            let (left, right) = tree.children.as_ref().unwrap();
+            size /= 2;
+            let went_left = i <= size;
+            path.push((tree, went_left));
+            if went_left {
+                tree = left;
+                i -= 1;
+            } else {
+                tree = right;
+                i -= 1 + size;
+            }
+        }
+
+        //Back up, each copied node takes the new subtree on its side and shares the other
+        let mut new_tree = P::new(Tree {
+            elem,
+            children: tree.children.clone(),
+        });
+        for (node, went_left) in path.into_iter().rev() {
+            let (left, right) = node.children.as_ref().unwrap();
+            let children = if went_left {
+                (new_tree, right.clone())
+            } else {
+                (left.clone(), new_tree)
+            };
+            new_tree = P::new(Tree {
+                elem: node.elem.clone(),
+                children: Some(children),
+            });
+        }
+        let mut head = Self::push(spine.size, new_tree, spine.next.clone());
+        for spine in before.into_iter().rev() {
+            head = Self::push(spine.size, spine.tree.clone(), head);
+        }
+        Some(GenericSkewList { head })
+    }
+}
+
+impl<T, P: PointerKind> Clone for GenericSkewList<T, P> {
+    fn clone(&self) -> Self {
+        GenericSkewList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T, P: PointerKind> Default for GenericSkewList<T, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, P: PointerKind> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+
+    //Preorder, right subtree pushed first so the left one comes out first
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.trees.is_empty() {
+            let spine = self.spine?;
+            self.trees.push(&spine.tree);
+            self.spine = spine.next.as_deref();
+        }
+        let tree = self.trees.pop()?;
+        if let Some((left, right)) = &tree.children {
+            self.trees.push(right);
+            self.trees.push(left);
+        }
+        Some(&tree.elem)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArcSkewList, SkewList};
+    use std::rc::Rc;
+
+    //n, n - 1, ..., 1
+    fn countdown(n: usize) -> SkewList<usize> {
+        (1..=n).fold(SkewList::new(), |list, i| list.cons(i))
+    }
+
+    #[test]
+    fn basics() {
+        let list = SkewList::new();
+        assert_eq!(list.head(), None);
+        assert!(list.tail().is_empty());
+
+        let list = list.cons(1).cons(2).cons(3);
+        assert_eq!(list.head(), Some(&3));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.get(2), Some(&1));
+        assert_eq!(list.get(3), None);
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&2));
+        let list = list.tail().tail();
+        assert_eq!(list.head(), None);
+    }
+
+    #[test]
+    fn indexing() {
+        for n in 0..100 {
+            let list = countdown(n);
+            assert_eq!(list.len(), n);
+            let expected: Vec<usize> = (1..=n).rev().collect();
+            assert_eq!(list.iter().cloned().collect::<Vec<_>>(), expected);
+            for (i, x) in expected.iter().enumerate() {
+                assert_eq!(list.get(i), Some(x));
+            }
+            //tail keeps every index in place, shifted by one
+            let tail = list.tail();
+            for i in 0..n.saturating_sub(1) {
+                assert_eq!(tail.get(i), list.get(i + 1));
+            }
+        }
+    }
+
+    #[test]
+    fn update() {
+        let old = countdown(50);
+        for i in 0..50 {
+            let new = old.update(i, 1000).unwrap();
+            assert_eq!(new.get(i), Some(&1000));
+            assert_eq!(new.len(), 50);
+            for j in (0..50).filter(|&j| j != i) {
+                assert_eq!(new.get(j), old.get(j));
+            }
+        }
+        //The old version is untouched
+        assert_eq!(
+            old.iter().cloned().collect::<Vec<_>>(),
+            (1..=50).rev().collect::<Vec<_>>()
+        );
+        assert!(old.update(50, 0).is_none());
+
+        //Trees in front of and behind the updated one are shared, not copied
+        let new = old.update(40, 0).unwrap();
+        let (mut old_link, mut new_link) = (&old.head, &new.head);
+        let mut start = 0;
+        while let (Some(o), Some(n)) = (old_link, new_link) {
+            if start + o.size <= 40 {
+                assert!(!Rc::ptr_eq(o, n));
+                assert!(Rc::ptr_eq(&o.tree, &n.tree));
+            } else if start <= 40 {
+                assert!(!Rc::ptr_eq(&o.tree, &n.tree));
+            } else {
+                assert!(Rc::ptr_eq(o, n));
+                break;
+            }
+            start += o.size;
+            old_link = &o.next;
+            new_link = &n.next;
+        }
+    }
+
+    #[test]
+    fn arc() {
+        let list = (0..10).fold(ArcSkewList::new(), |list, i| list.cons(i));
+        let worker = {
+            let list = list.clone();
+            std::thread::spawn(move || list.update(3, 100).unwrap().iter().sum::<i32>())
+        };
+        assert_eq!(worker.join().unwrap(), 45 - 6 + 100);
+        assert_eq!(list.get(3), Some(&6));
+    }
+
+    #[test]
+    fn long() {
+        let n = 1_000_000;
+        let mut list = countdown(n);
+        for i in (0..n).step_by(9973) {
+            assert_eq!(list.get(i), Some(&(n - i)));
+            list = list.update(i, 0).unwrap();
+            assert_eq!(list.get(i), Some(&0));
+        }
+        assert_eq!(list.len(), n);
+    }
+}
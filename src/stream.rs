@@ -0,0 +1,311 @@
+//Lazy persistent stream, the lazy cousin of third::List
+//Every cell starts out as a thunk, a closure that works out (head, rest of the stream)
+//the first time somebody looks. The result goes into a OnceCell, so a thunk runs at
+//most once and every clone of the stream sees the same value afterwards.
+//map, filter, zip and take just wrap the stream they start from in new thunks and
+//don't force anything, so they work on infinite streams. Forcing a cell forces the
+//same cell of the streams underneath it, one per combinator, so that never gets deep.
+//Everything is Rc based, like third::List
+
+use std::cell::{Cell, OnceCell};
+use std::rc::Rc;
+
+use crate::third::List;
+
+pub struct Stream<T> {
+    cell: Rc<Suspension<T>>,
+}
+
+type Node<T> = Option<(T, Stream<T>)>;
+type Thunk<T> = Box<dyn FnOnce() -> Node<T>>;
+
+struct Suspension<T> {
+    value: OnceCell<Node<T>>,
+    //Taken out and run by the first force
+    thunk: Cell<Option<Thunk<T>>>,
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Stream<T>>,
+}
+
+impl<T> Stream<T> {
+    fn forced(node: Node<T>) -> Self {
+        Stream {
+            cell: Rc::new(Suspension {
+                value: OnceCell::from(node),
+                thunk: Cell::new(None),
+            }),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self::forced(None)
+    }
+
+    //Head known right away, rest as it is
+    pub fn cons(elem: T, rest: Stream<T>) -> Self {
+        Self::forced(Some((elem, rest)))
+    }
+
+    //Runs the thunk the first time the stream gets looked at
+    pub fn lazy(thunk: impl FnOnce() -> Node<T> + 'static) -> Self {
+        Stream {
+            cell: Rc::new(Suspension {
+                value: OnceCell::new(),
+                thunk: Cell::new(Some(Box::new(thunk))),
+            }),
+        }
+    }
+
+    fn force(&self) -> &Node<T> {
+        self.cell.value.get_or_init(|| {
+            let thunk = self.cell.thunk.take();
+            thunk.expect("stream cell forced itself")()
+        })
+    }
+
+    pub fn is_forced(&self) -> bool {
+        self.cell.value.get().is_some()
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.force().as_ref().map(|(elem, _)| elem)
+    }
+
+    //Like third::List::tail, the tail of an empty stream is empty
+    pub fn tail(&self) -> Stream<T> {
+        match self.force() {
+            Some((_, rest)) => rest.clone(),
+            None => Self::empty(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.force().is_none()
+    }
+
+    //Forces as it goes
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: Some(self) }
+    }
+}
+
+impl<T: 'static> Stream<T> {
+    //f(state) gives the next element and the state after it, None ends the stream
+    pub fn unfold<S: 'static>(state: S, f: impl Fn(S) -> Option<(T, S)> + 'static) -> Self {
+        fn from<T: 'static, S: 'static>(state: S, f: Rc<dyn Fn(S) -> Option<(T, S)>>) -> Stream<T> {
+            Stream::lazy(move || {
+                let (elem, state) = f(state)?;
+                Some((elem, from(state, f)))
+            })
+        }
+        from(state, Rc::new(f))
+    }
+
+    pub fn map<U: 'static>(&self, f: impl Fn(&T) -> U + 'static) -> Stream<U> {
+        fn from<T: 'static, U: 'static>(stream: Stream<T>, f: Rc<dyn Fn(&T) -> U>) -> Stream<U> {
+            Stream::lazy(move || {
+                let (elem, rest) = stream.force().as_ref()?;
+                Some((f(elem), from(rest.clone(), f)))
+            })
+        }
+        from(self.clone(), Rc::new(f))
+    }
+}
+
+//The cells of these hold the same elements as the stream they come from, so the
+//elements get cloned
+impl<T: Clone + 'static> Stream<T> {
+    //seed, f(seed), f(f(seed)), ...
+    pub fn iterate(seed: T, f: impl Fn(&T) -> T + 'static) -> Self {
+        Self::unfold(seed, move |x| {
+            let next = f(&x);
+            Some((x, next))
+        })
+    }
+
+    //The first n elements, or all of them if there are fewer
+    pub fn take(&self, n: usize) -> Self {
+        let stream = self.clone();
+        Stream::lazy(move || match stream.force() {
+            Some((elem, rest)) if n > 0 => Some((elem.clone(), rest.take(n - 1))),
+            _ => None,
+        })
+    }
+
+    //Skips ahead in a loop inside the thunk, so a long run of rejected elements
+    //doesn't nest. It does force all of them though, an infinite stream with nothing
+    //left that passes never comes back
+    pub fn filter(&self, f: impl Fn(&T) -> bool + 'static) -> Self {
+        fn from<T: Clone + 'static>(mut stream: Stream<T>, f: Rc<dyn Fn(&T) -> bool>) -> Stream<T> {
+            Stream::lazy(move || loop {
+                let rest = match stream.force() {
+                    None => return None,
+                    Some((elem, rest)) if f(elem) => {
+                        return Some((elem.clone(), from(rest.clone(), f)))
+                    }
+                    Some((_, rest)) => rest.clone(),
+                };
+                stream = rest;
+            })
+        }
+        from(self.clone(), Rc::new(f))
+    }
+
+    //As long as the shorter of the two
+    pub fn zip<U: Clone + 'static>(&self, other: &Stream<U>) -> Stream<(T, U)> {
+        let (a, b) = (self.clone(), other.clone());
+        Stream::lazy(move || {
+            let (x, rest_a) = a.force().as_ref()?;
+            let (y, rest_b) = b.force().as_ref()?;
+            Some(((x.clone(), y.clone()), rest_a.zip(rest_b)))
+        })
+    }
+
+    //self, then other. Lazy like the rest, a cell of the result forces only the
+    //matching cell of self, or the first one of other once self has run out
+    pub fn concat(&self, other: &Stream<T>) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+        Stream::lazy(move || match a.force() {
+            Some((elem, rest)) => Some((elem.clone(), rest.concat(&b))),
+            None => b.force().clone(),
+        })
+    }
+
+    //Forces the first n elements (fewer if the stream ends) into a third::List,
+    //with the first element of the stream at its head
+    pub fn to_list(&self, n: usize) -> List<T> {
+        let prefix: Vec<&T> = self.iter().take(n).collect();
+        prefix
+            .into_iter()
+            .rev()
+            .fold(List::new(), |list, elem| list.append(elem.clone()))
+    }
+}
+
+//Another handle on the same cells, forcing through one forces for all of them
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        Stream {
+            cell: Rc::clone(&self.cell),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (elem, rest) = self.next?.force().as_ref()?;
+        self.next = Some(rest);
+        Some(elem)
+    }
+}
+
+//Same problem as third::List, a long forced stream would drop one cell inside the
+//other. Cells nobody else uses get their value taken out first, so every drop is shallow
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        let mut next = match Rc::get_mut(&mut self.cell).and_then(|cell| cell.value.take()) {
+            Some(Some((_, rest))) => rest,
+            _ => return,
+        };
+        while let Some(Some((_, rest))) =
+            Rc::get_mut(&mut next.cell).and_then(|cell| cell.value.take())
+        {
+            next = rest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Stream;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn naturals() -> Stream<u64> {
+        Stream::iterate(0, |x| x + 1)
+    }
+
+    fn first<T: Clone + 'static>(stream: &Stream<T>, n: usize) -> Vec<T> {
+        stream.iter().take(n).cloned().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let stream = Stream::cons(1, Stream::cons(2, Stream::empty()));
+        assert_eq!(stream.head(), Some(&1));
+        assert_eq!(stream.tail().head(), Some(&2));
+        assert!(stream.tail().tail().is_empty());
+        assert!(stream.tail().tail().tail().is_empty());
+        assert_eq!(Stream::<i32>::empty().head(), None);
+
+        let fib = Stream::unfold((0u64, 1u64), |(a, b)| Some((a, (b, a + b))));
+        assert_eq!(first(&fib, 10), vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+        let countdown = Stream::unfold(3, |n| if n == 0 { None } else { Some((n, n - 1)) });
+        assert_eq!(first(&countdown, 10), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn memoized() {
+        //Counts how often the thunk runs
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let stream = Stream::unfold(0, move |n| {
+            counter.set(counter.get() + 1);
+            Some((n, n + 1))
+        });
+        assert!(!stream.is_forced());
+        assert_eq!(calls.get(), 0);
+
+        let copy = stream.clone();
+        assert_eq!(first(&stream, 5), vec![0, 1, 2, 3, 4]);
+        assert_eq!(calls.get(), 5);
+        //Same cells, nothing runs again
+        assert_eq!(first(&copy, 5), vec![0, 1, 2, 3, 4]);
+        assert_eq!(first(&stream.tail(), 3), vec![1, 2, 3]);
+        assert_eq!(calls.get(), 5);
+        assert!(copy.is_forced());
+    }
+
+    #[test]
+    fn combinators() {
+        let evens = naturals().filter(|x| x % 2 == 0);
+        let squares = naturals().map(|x| x * x);
+        assert_eq!(first(&evens, 4), vec![0, 2, 4, 6]);
+        assert_eq!(first(&squares, 4), vec![0, 1, 4, 9]);
+        assert_eq!(first(&evens.zip(&squares), 3), vec![(0, 0), (2, 1), (4, 4)]);
+        assert_eq!(first(&naturals().take(3), 10), vec![0, 1, 2]);
+        assert!(naturals().take(0).is_empty());
+        let joined = naturals().take(2).concat(&squares);
+        assert_eq!(first(&joined, 5), vec![0, 1, 0, 1, 4]);
+        assert!(Stream::<u64>::empty().concat(&Stream::empty()).is_empty());
+
+        //Nothing gets forced until somebody looks
+        let source = naturals();
+        let mapped = source.map(|x| x + 1).filter(|x| x % 3 == 0).take(2);
+        assert!(!source.is_forced());
+        assert_eq!(first(&mapped, 5), vec![3, 6]);
+        assert!(source.is_forced());
+
+        let list = squares.to_list(4);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 4, 9]);
+        assert_eq!(naturals().take(2).to_list(10).len(), 2);
+    }
+
+    #[test]
+    fn long() {
+        let n = 1_000_000;
+        let stream = naturals();
+        assert_eq!(stream.iter().nth(n), Some(&(n as u64)));
+        //A long run of rejected elements in a single filter thunk
+        let sparse = stream.filter(move |&x| x == n as u64);
+        assert_eq!(sparse.head(), Some(&(n as u64)));
+        let list = stream.map(|x| x * 2).to_list(n);
+        assert_eq!(list.len(), n);
+        //Dropping a million forced cells doesn't overflow
+        drop(stream);
+    }
+}
@@ -1,28 +1,81 @@
+use std::collections::HashMap;
+use std::ops::Deref;
 use std::rc::Rc;
+use std::sync::Arc;
 
-pub struct List<T> {
-    head: Link<T>,
+mod intern;
+pub use self::intern::Interner;
+
+// The list only ever needs a few things from its pointers: make one, clone it (bump
+// the count), read the count and take the node back out if nobody else points at it.
+// PointerKind picks Rc or Arc for all of those, so there is one list and the two flavours
+// can't drift apart. Arc pays for atomic counting but is Send + Sync when T is.
+
+pub trait PointerKind {
+    type Pointer<T>: Deref<Target = T> + Clone;
+    fn new<T>(value: T) -> Self::Pointer<T>;
+    fn into_inner<T>(pointer: Self::Pointer<T>) -> Option<T>;
+    fn strong_count<T>(pointer: &Self::Pointer<T>) -> usize;
+}
+
+pub struct RcPointer;
+pub struct ArcPointer;
+
+impl PointerKind for RcPointer {
+    type Pointer<T> = Rc<T>;
+    fn new<T>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+    fn into_inner<T>(pointer: Rc<T>) -> Option<T> {
+        Rc::into_inner(pointer)
+    }
+    fn strong_count<T>(pointer: &Rc<T>) -> usize {
+        Rc::strong_count(pointer)
+    }
+}
+
+impl PointerKind for ArcPointer {
+    type Pointer<T> = Arc<T>;
+    fn new<T>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+    //Not try_unwrap: two threads dropping the last two handles could both see a count
+    //of 2, both fail, and the node would then be freed by the recursive Drop of Arc
+    fn into_inner<T>(pointer: Arc<T>) -> Option<T> {
+        Arc::into_inner(pointer)
+    }
+    fn strong_count<T>(pointer: &Arc<T>) -> usize {
+        Arc::strong_count(pointer)
+    }
+}
+
+// Aliases rather than a default type parameter, so List::new() still knows its pointer
+pub type List<T> = GenericList<T, RcPointer>;
+pub type ArcList<T> = GenericList<T, ArcPointer>;
+
+pub struct GenericList<T, P: PointerKind> {
+    head: Link<T, P>,
 }
 
-pub struct Iter<'a, T> {
-    next: Option<&'a Node<T>>,
+pub struct Iter<'a, T, P: PointerKind> {
+    next: Option<&'a Node<T, P>>,
 }
 
-type Link<T> = Option<Rc<Node<T>>>;
+type Link<T, P> = Option<<P as PointerKind>::Pointer<Node<T, P>>>;
 
-struct Node<T> {
+struct Node<T, P: PointerKind> {
     elem: T,
-    next: Link<T>,
+    next: Link<T, P>,
 }
 
-impl<T> List<T> {
+impl<T, P: PointerKind> GenericList<T, P> {
     pub fn new() -> Self {
-        List { head: None }
+        GenericList { head: None }
     }
-    pub fn append(&self, elem: T) -> List<T> {
-        List {
-            head: Some(Rc::new(Node {
-                elem: elem,
+    pub fn append(&self, elem: T) -> GenericList<T, P> {
+        GenericList {
+            head: Some(P::new(Node {
+                elem,
                 next: self.head.clone(),
             })),
         }
@@ -31,11 +84,11 @@ impl<T> List<T> {
     //tail is the logical inverse of append operation. It takes a list and returns
     //the whole list with the first element removed. All that
     //is cloning the second element in the list (if it exists).
-    pub fn tail(&self) -> List<T> {
+    pub fn tail(&self) -> GenericList<T, P> {
         //Returning Option<T>
         // Thankfully, this is another common Option pattern,
         // and we can just use and_then to let us return an Option.
-        List {
+        GenericList {
             head: self.head.as_ref().and_then(|node| node.next.clone()),
         }
     }
@@ -44,14 +97,220 @@ impl<T> List<T> {
         self.head.as_ref().map(|node| &node.elem)
     }
 
-    pub fn iter(&self) -> Iter<'_, T> {
+    pub fn iter(&self) -> Iter<'_, T, P> {
         Iter {
             next: self.head.as_deref(),
         }
     }
+
+    //Puts elems in front of rest, the first of elems ends up as the head.
+    //The list gets built back to front so nothing recurses
+    fn prepend_all(elems: Vec<T>, rest: Link<T, P>) -> Self {
+        let mut head = rest;
+        for elem in elems.into_iter().rev() {
+            head = Some(P::new(Node { elem, next: head }));
+        }
+        GenericList { head }
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    pub fn fold<A>(&self, init: A, f: impl FnMut(A, &T) -> A) -> A {
+        self.iter().fold(init, f)
+    }
+
+    //Every element is new, so nothing can be shared
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> GenericList<U, P> {
+        GenericList::prepend_all(self.iter().map(f).collect(), None)
+    }
+
+    //Everything after the first n nodes, shared as it is. O(n) to walk there and
+    //nothing gets allocated
+    pub fn drop(&self, n: usize) -> Self {
+        let mut link = &self.head;
+        for _ in 0..n {
+            match link {
+                Some(node) => link = &node.next,
+                None => break,
+            }
+        }
+        GenericList { head: link.clone() }
+    }
+
+    //Same first node, so the very same elements all the way down. Two empty lists count
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => std::ptr::eq::<Node<T, P>>(&**a, &**b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    //How many nodes at the end both lists have in common (the same nodes, not just
+    //equal elements). Once two lists meet they stay together, so after lining up the
+    //ends it's the first spot where they point at the same node
+    pub fn shared_suffix_len(&self, other: &Self) -> usize {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        let (la, lb) = (a.len(), b.len());
+        if la > lb {
+            a = a.drop(la - lb);
+        } else {
+            b = b.drop(lb - la);
+        }
+        let mut len = la.min(lb);
+        while !a.ptr_eq(&b) {
+            a = a.tail();
+            b = b.tail();
+            len -= 1;
+        }
+        len
+    }
+
+    //Number of pointers to node n: the list it starts, the nodes in front of it in
+    //other lists, and whatever else holds on to it. Drop stops at the first node above 1
+    pub fn strong_count_at(&self, n: usize) -> Option<usize> {
+        let mut link = &self.head;
+        for _ in 0..n {
+            link = &link.as_ref()?.next;
+        }
+        link.as_ref().map(P::strong_count)
+    }
+}
+
+//Everything that builds new nodes in front of shared ones has to copy elements
+impl<T: Clone, P: PointerKind> GenericList<T, P> {
+    //Shares the longest tail where every element passes, only what comes before
+    //the last rejected element gets copied
+    pub fn filter(&self, mut f: impl FnMut(&T) -> bool) -> Self {
+        let mut copied = vec![];
+        //Elements that passed since the last rejected one, they might be shared yet
+        let mut passed = vec![];
+        let mut shared = self.head.clone();
+        let mut link = &self.head;
+        while let Some(node) = link {
+            if f(&node.elem) {
+                passed.push(&node.elem);
+            } else {
+                copied.append(&mut passed);
+                shared = node.next.clone();
+            }
+            link = &node.next;
+        }
+        Self::prepend_all(copied.into_iter().cloned().collect(), shared)
+    }
+
+    pub fn reverse(&self) -> Self {
+        self.fold(Self::new(), |list, elem| list.append(elem.clone()))
+    }
+
+    //Copies self in front of other, other is shared as a whole
+    pub fn concat(&self, other: &Self) -> Self {
+        Self::prepend_all(self.iter().cloned().collect(), other.head.clone())
+    }
+
+    //Shares everything if the list has at most n elements, copies n otherwise
+    pub fn take(&self, n: usize) -> Self {
+        let front: Vec<T> = self.iter().take(n + 1).cloned().collect();
+        if front.len() <= n {
+            return self.clone();
+        }
+        Self::prepend_all(front.into_iter().take(n).collect(), None)
+    }
+
+    //As long as the shorter of the two
+    pub fn zip<U: Clone>(&self, other: &GenericList<U, P>) -> GenericList<(T, U), P> {
+        GenericList::prepend_all(
+            self.iter().cloned().zip(other.iter().cloned()).collect(),
+            None,
+        )
+    }
+}
+
+//What a set of lists costs in nodes compared to copying every one of them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SharingReport {
+    //Sum of the lengths, the nodes it would take without sharing
+    pub total: usize,
+    //Nodes actually there
+    pub distinct: usize,
+    //Nodes only one of the lists reaches
+    pub unique: usize,
+    //Nodes two or more of the lists reach
+    pub shared: usize,
+}
+
+//Walks the lists and counts every node once. Anything after a node that is shared
+//is shared too, so a walk stops at the first node already known to be shared. Every
+//node remembers how long the list is from there on, which gives the length of a list
+//as soon as it runs into an earlier one. So every node is visited at most twice, once
+//when it is new and once when it turns out to be shared
+pub fn sharing_report<T, P: PointerKind>(lists: &[&GenericList<T, P>]) -> SharingReport {
+    fn key<T, P: PointerKind>(node: &P::Pointer<Node<T, P>>) -> *const Node<T, P> {
+        &**node
+    }
+    let mut report = SharingReport::default();
+    //Node address to the length from that node on and whether more than one list
+    //reaches it
+    let mut seen: HashMap<*const Node<T, P>, (usize, bool)> = HashMap::new();
+    for list in lists {
+        //New nodes, up to the end or the first one an earlier list got to
+        let mut fresh = vec![];
+        let mut link = &list.head;
+        while let Some(node) = link {
+            if seen.contains_key(&key(node)) {
+                break;
+            }
+            fresh.push(key(node));
+            link = &node.next;
+        }
+        let rest = link.as_ref().map_or(0, |node| seen[&key(node)].0);
+        report.total += fresh.len() + rest;
+        for (i, &node) in fresh.iter().enumerate() {
+            seen.insert(node, (fresh.len() - i + rest, false));
+        }
+        //Everything from there on has been reached twice now
+        while let Some(node) = link {
+            let shared = &mut seen.get_mut(&key(node)).unwrap().1;
+            if *shared {
+                break;
+            }
+            *shared = true;
+            link = &node.next;
+        }
+    }
+    report.distinct = seen.len();
+    report.shared = seen.values().filter(|&&(_, shared)| shared).count();
+    report.unique = report.distinct - report.shared;
+    report
+}
+
+//Another handle on the same nodes, nothing gets copied. This is how a snapshot
+//gets handed to another thread
+impl<T, P: PointerKind> Clone for GenericList<T, P> {
+    fn clone(&self) -> Self {
+        GenericList {
+            head: self.head.clone(),
+        }
+    }
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<T, P: PointerKind> Default for GenericList<T, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, P: PointerKind> Iterator for Iter<'a, T, P> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -62,11 +321,11 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
-impl<T> Drop for List<T> {
+impl<T, P: PointerKind> Drop for GenericList<T, P> {
     fn drop(&mut self) {
         let mut head = self.head.take();
         while let Some(node) = head {
-            if let Ok(mut node) = Rc::try_unwrap(node) {
+            if let Some(mut node) = P::into_inner(node) {
                 head = node.next.take();
             } else {
                 break;
@@ -77,7 +336,11 @@ impl<T> Drop for List<T> {
 
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{sharing_report, ArcList, List, RcPointer, SharingReport};
+    use std::rc::Rc;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
     #[test]
     fn basics() {
         let list = List::new();
@@ -100,6 +363,7 @@ mod test {
         assert_eq!(list.head(), None);
     }
 
+    #[test]
     fn iter() {
         let list = List::new().append(1).append(2).append(3);
         let mut iter = list.iter();
@@ -107,4 +371,188 @@ mod test {
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&1));
     }
+
+    #[test]
+    fn arc() {
+        fn send_sync<T: Send + Sync>(_: &T) {}
+        let list = ArcList::new().append(1).append(2).append(3);
+        send_sync(&list);
+        assert_eq!(list.head(), Some(&3));
+        assert_eq!(list.tail().head(), Some(&2));
+
+        //Every thread builds on the same snapshot without seeing the others
+        let workers: Vec<_> = (0..4)
+            .map(|i| {
+                let snapshot = list.clone();
+                thread::spawn(move || snapshot.append(i * 10).iter().sum::<i32>())
+            })
+            .collect();
+        let sums: Vec<i32> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_eq!(sums, vec![6, 16, 26, 36]);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn concurrent_drop() {
+        //Threads letting go of the same long list at once, one of them has to end up
+        //walking it and none may recurse
+        let mut list = ArcList::new();
+        for i in 0..200_000 {
+            list = list.append(i);
+        }
+        let start = Arc::new(Barrier::new(4));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let (snapshot, start) = (list.clone(), start.clone());
+                thread::spawn(move || {
+                    start.wait();
+                    drop(snapshot);
+                })
+            })
+            .collect();
+        drop(list);
+        for w in workers {
+            w.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn long_drop() {
+        //Drop walks the list instead of recursing, for both pointer kinds
+        let mut list = List::new();
+        let mut arc = ArcList::new();
+        for i in 0..200_000 {
+            list = list.append(i);
+            arc = arc.append(i);
+        }
+        drop(list);
+        drop(arc);
+    }
+
+    //Checks two lists start at the very same node, not just equal elements
+    fn same_nodes<T>(a: &List<T>, b: &List<T>) -> bool {
+        match (&a.head, &b.head) {
+            (Some(x), Some(y)) => Rc::ptr_eq(x, y),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn items<T: Clone>(list: &List<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn combinators() {
+        let list = List::new().append(4).append(3).append(2).append(1);
+        assert_eq!(list.len(), 4);
+        assert!(!list.is_empty());
+        assert_eq!(list.nth(2), Some(&3));
+        assert_eq!(list.nth(4), None);
+        assert_eq!(list.fold(0, |sum, x| sum * 10 + x), 1234);
+        assert_eq!(items(&list.map(|x| x * x)), vec![1, 4, 9, 16]);
+        assert_eq!(items(&list.reverse()), vec![4, 3, 2, 1]);
+        assert_eq!(items(&list.take(2)), vec![1, 2]);
+        assert_eq!(items(&list.drop(3)), vec![4]);
+        assert!(list.drop(10).is_empty());
+        assert_eq!(items(&list.filter(|x| x % 2 == 0)), vec![2, 4]);
+
+        let other = List::new().append("b").append("a");
+        assert_eq!(items(&list.zip(&other)), vec![(1, "a"), (2, "b")]);
+        assert_eq!(items(&list.concat(&list)), vec![1, 2, 3, 4, 1, 2, 3, 4]);
+        assert_eq!(List::<i32>::new().len(), 0);
+    }
+
+    #[test]
+    fn sharing() {
+        let list = List::new().append(4).append(3).append(2).append(1);
+        //drop and take too far hand back nodes of the original
+        assert!(same_nodes(&list.drop(2), &list.tail().tail()));
+        assert!(same_nodes(&list.take(4), &list));
+        //concat shares the whole second list
+        let joined = List::new().append(0).concat(&list);
+        assert!(same_nodes(&joined.tail(), &list));
+        //filter shares everything after the last element it threw out
+        assert!(!same_nodes(&list.filter(|&x| x != 2), &list.drop(2)));
+        assert!(same_nodes(&list.filter(|&x| x != 2).tail(), &list.drop(2)));
+        assert!(same_nodes(&list.filter(|_| true), &list));
+    }
+
+    #[test]
+    fn long() {
+        let n = 1_000_000;
+        let list = (0..n).fold(List::new(), |list, i| list.append(i));
+        assert_eq!(list.len(), n);
+        assert_eq!(list.map(|x| x + 1).head(), Some(&n));
+        assert_eq!(list.reverse().head(), Some(&0));
+        assert_eq!(list.filter(|x| x % 2 == 0).len(), n / 2);
+        assert_eq!(list.concat(&list).len(), 2 * n);
+        assert_eq!(list.zip(&list.tail()).len(), n - 1);
+        assert_eq!(list.take(n - 1).len(), n - 1);
+        assert_eq!(list.drop(n - 1).head(), Some(&0));
+    }
+
+    #[test]
+    fn introspection() {
+        let base = List::new().append(1).append(2).append(3);
+        let left = base.append(10).append(11);
+        let right = base.append(20);
+        let copy = List::new().append(1).append(2).append(3);
+
+        assert!(base.ptr_eq(&base.clone()));
+        assert!(left.tail().tail().ptr_eq(&base));
+        assert!(!base.ptr_eq(&copy));
+        assert!(List::<i32>::new().ptr_eq(&List::new()));
+
+        assert_eq!(left.shared_suffix_len(&right), 3);
+        assert_eq!(right.shared_suffix_len(&left), 3);
+        assert_eq!(left.shared_suffix_len(&base), 3);
+        assert_eq!(base.shared_suffix_len(&copy), 0);
+        assert_eq!(left.shared_suffix_len(&left.tail()), 4);
+
+        //base, left's 10 and right's 20 all point at the 3
+        assert_eq!(base.strong_count_at(0), Some(3));
+        assert_eq!(left.strong_count_at(0), Some(1));
+        assert_eq!(left.strong_count_at(2), Some(3));
+        assert_eq!(base.strong_count_at(1), Some(1));
+        assert_eq!(base.strong_count_at(3), None);
+        drop(right);
+        assert_eq!(base.strong_count_at(0), Some(2));
+    }
+
+    #[test]
+    fn report() {
+        let base = List::new().append(1).append(2).append(3);
+        let left = base.append(10).append(11);
+        let right = base.append(20);
+        let report = sharing_report(&[&base, &left, &right]);
+        assert_eq!(
+            report,
+            SharingReport {
+                total: 3 + 5 + 4,
+                distinct: 6,
+                unique: 3,
+                shared: 3,
+            }
+        );
+        //Same list twice shares everything
+        let report = sharing_report(&[&left, &left]);
+        assert_eq!((report.distinct, report.shared, report.total), (5, 5, 10));
+        //A suffix of an earlier list starts out on known nodes, the empty one has none
+        let report = sharing_report(&[&left, &base.tail(), &List::new()]);
+        assert_eq!((report.distinct, report.shared, report.total), (5, 2, 7));
+        assert_eq!(
+            sharing_report::<i32, RcPointer>(&[]),
+            SharingReport::default()
+        );
+
+        //A thousand versions on one long base cost a node each
+        let long = (0..100_000).fold(List::new(), |list, i| list.append(i));
+        let versions: Vec<List<i32>> = (0..1000).map(|i| long.append(i)).collect();
+        let refs: Vec<&List<i32>> = versions.iter().collect();
+        let report = sharing_report(&refs);
+        assert_eq!(report.distinct, 100_000 + 1000);
+        assert_eq!(report.unique, 1000);
+        assert_eq!(report.total, 1000 * 100_001);
+    }
 }
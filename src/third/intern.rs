@@ -0,0 +1,189 @@
+//Hash consing for third::List. The interner remembers every node it made, so asking
+//for a node with the same element in front of the same tail hands back the one that
+//exists already instead of making a new one. Build lists only through one interner
+//and equal lists are the very same nodes: ptr_eq is list equality, and a million
+//lists with the same suffix keep a single copy of it.
+//Tails that didn't come from the interner still work, they just count as different
+//from every other tail.
+//The table holds Weak pointers, it never keeps a node alive. Dropped nodes leave dead
+//entries behind, they are cleared out of a bucket whenever it gets looked at, and the
+//whole table gets swept once it holds twice as many entries as after the last sweep
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::rc::{Rc, Weak};
+
+use super::{GenericList, List, Node, RcPointer};
+
+type RcNode<T> = Node<T, RcPointer>;
+
+pub struct Interner<T> {
+    //Keyed by the hash of (element, address of the tail), the nodes in a bucket get
+    //compared for real so the elements don't have to be stored twice
+    buckets: HashMap<u64, Vec<Weak<RcNode<T>>>>,
+    hasher: RandomState,
+    //Entries in the table, dead ones included
+    entries: usize,
+    sweep_at: usize,
+}
+
+//Below this the table isn't worth sweeping
+const MIN_SWEEP: usize = 64;
+
+fn tail_address<T>(tail: &Option<Rc<RcNode<T>>>) -> usize {
+    tail.as_ref().map_or(0, |node| Rc::as_ptr(node) as usize)
+}
+
+impl<T: Eq + Hash> Interner<T> {
+    pub fn new() -> Self {
+        Interner {
+            buckets: HashMap::new(),
+            hasher: RandomState::new(),
+            entries: 0,
+            sweep_at: MIN_SWEEP,
+        }
+    }
+
+    //Interned List::append
+    pub fn cons(&mut self, elem: T, tail: &List<T>) -> List<T> {
+        let key = self.hasher.hash_one((&elem, tail_address(&tail.head)));
+        let bucket = self.buckets.entry(key).or_default();
+        let before = bucket.len();
+        bucket.retain(|node| node.strong_count() > 0);
+        self.entries -= before - bucket.len();
+        for node in bucket.iter() {
+            if let Some(node) = node.upgrade() {
+                if node.elem == elem && tail_address(&node.next) == tail_address(&tail.head) {
+                    return GenericList { head: Some(node) };
+                }
+            }
+        }
+        let node = Rc::new(Node {
+            elem,
+            next: tail.head.clone(),
+        });
+        bucket.push(Rc::downgrade(&node));
+        self.entries += 1;
+        if self.entries >= self.sweep_at {
+            self.purge();
+        }
+        GenericList { head: Some(node) }
+    }
+
+    //Interned copy of any list, built from the end so every tail is interned
+    //before the node in front of it
+    pub fn intern(&mut self, list: &List<T>) -> List<T>
+    where
+        T: Clone,
+    {
+        let elems: Vec<&T> = list.iter().collect();
+        elems
+            .into_iter()
+            .rev()
+            .fold(List::new(), |tail, elem| self.cons(elem.clone(), &tail))
+    }
+
+    //Nodes in the table that are still alive
+    pub fn len(&self) -> usize {
+        self.buckets
+            .values()
+            .flatten()
+            .filter(|node| node.strong_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    //Entries the table is holding on to, dead ones included
+    pub fn capacity(&self) -> usize {
+        self.entries
+    }
+
+    //Drops every entry of a node that's gone
+    pub fn purge(&mut self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(|node| node.strong_count() > 0);
+            !bucket.is_empty()
+        });
+        self.entries = self.buckets.values().map(Vec::len).sum();
+        self.sweep_at = (2 * self.entries).max(MIN_SWEEP);
+    }
+}
+
+impl<T: Eq + Hash> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{sharing_report, List};
+    use super::Interner;
+
+    #[test]
+    fn sharing() {
+        let mut interner = Interner::new();
+        let two = interner.cons(2, &List::new());
+        let a = interner.cons(1, &two);
+        let two_again = interner.cons(2, &List::new());
+        let b = interner.cons(1, &two_again);
+        assert!(two.ptr_eq(&two_again));
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&interner.cons(2, &two)));
+        let three = interner.cons(3, &List::new());
+        assert!(!a.ptr_eq(&interner.cons(1, &three)));
+
+        //Lists built the usual way come out the same once interned
+        let plain = List::new().append(2).append(1);
+        assert!(!plain.ptr_eq(&a));
+        assert!(interner.intern(&plain).ptr_eq(&a));
+        //2, 1 -> 2 and 3 are alive, the temporaries are gone already
+        assert_eq!(interner.len(), 3);
+
+        //A tail from outside is only ever equal to itself
+        let outside = List::new().append(2);
+        let c = interner.cons(1, &outside);
+        assert!(!c.ptr_eq(&a));
+        assert!(c.ptr_eq(&interner.cons(1, &outside)));
+    }
+
+    #[test]
+    fn memory() {
+        //Lots of lists with the same long suffix keep a single copy of it
+        let mut interner = Interner::new();
+        let lists: Vec<List<u32>> = (0..1000)
+            .map(|i| {
+                let plain = (0..100)
+                    .fold(List::new(), |list, x| list.append(x))
+                    .append(i);
+                interner.intern(&plain)
+            })
+            .collect();
+        let refs: Vec<&List<u32>> = lists.iter().collect();
+        let report = sharing_report(&refs);
+        assert_eq!(report.distinct, 100 + 1000);
+        assert_eq!(interner.len(), 100 + 1000);
+    }
+
+    #[test]
+    fn cleanup() {
+        let mut interner = Interner::new();
+        let kept = interner.intern(&List::new().append(1).append(2));
+        for i in 0..100_000 {
+            let list = interner.cons(i, &kept);
+            drop(list);
+        }
+        //Dead entries get swept as the table grows, it never holds much more than twice
+        //what's alive after a sweep
+        assert!(interner.capacity() <= 2 * 64);
+        assert_eq!(interner.len(), 2);
+        drop(kept);
+        interner.purge();
+        assert_eq!(interner.capacity(), 0);
+        assert!(interner.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+//Disjoint set forest over the indices 0..n
+//Every set is a tree and the root of the tree is the representative of the set.
+//Two tricks keep the trees flat, which makes find and union practically O(1):
+//  Path compression: every node visited by find is hooked straight onto the root
+//  Union by rank: the shallower tree always goes under the deeper one
+
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    sets: usize,
+}
+
+impl UnionFind {
+    //Every element starts out in a set of its own
+    pub fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            sets: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    //Number of disjoint sets left
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+
+    //Adds a new singleton set and returns its element
+    pub fn push(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.rank.push(0);
+        self.sets += 1;
+        x
+    }
+
+    //Two passes instead of recursion: one to find the root, one to compress the path
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut cur = x;
+        while self.parent[cur] != root {
+            let next = self.parent[cur];
+            self.parent[cur] = root;
+            cur = next;
+        }
+        root
+    }
+
+    //Returns false if both were already in the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.rank[a] < self.rank[b] {
+            self.parent[a] = b;
+        } else if self.rank[a] > self.rank[b] {
+            self.parent[b] = a;
+        } else {
+            self.parent[b] = a;
+            self.rank[a] += 1;
+        }
+        self.sets -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UnionFind;
+    #[test]
+    fn basics() {
+        let mut sets = UnionFind::new(5);
+        assert_eq!(sets.sets(), 5);
+        assert!(!sets.connected(0, 1));
+
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert_eq!(sets.sets(), 3);
+
+        assert!(sets.connected(0, 1));
+        assert!(!sets.connected(1, 3));
+
+        assert!(sets.union(1, 4));
+        assert!(sets.connected(0, 3));
+        assert_eq!(sets.sets(), 2);
+
+        let x = sets.push();
+        assert_eq!(x, 5);
+        assert_eq!(sets.len(), 6);
+        assert_eq!(sets.sets(), 3);
+        assert!(!sets.connected(x, 2));
+    }
+
+    #[test]
+    fn long_chain() {
+        //A long chain of unions should not blow the stack in find
+        let n = 1_000_000;
+        let mut sets = UnionFind::new(n);
+        for i in 1..n {
+            sets.union(i - 1, i);
+        }
+        assert_eq!(sets.sets(), 1);
+        assert!(sets.connected(0, n - 1));
+    }
+}
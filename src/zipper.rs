@@ -0,0 +1,203 @@
+//Zipper over a third::List: a list with a cursor in it
+//The elements left of the focus are kept in a list of their own, nearest first, and
+//the focus is the head of the list of everything from there on. Moving the focus
+//moves one element from one list to the other, editing only touches the head of
+//the right list, so everything is O(1) and shares both lists with the old zipper.
+//The focus can also sit right after the last element, where there is nothing to
+//replace or delete, but insert appends
+
+use crate::third::List;
+
+pub struct Zipper<T> {
+    left: List<T>,
+    right: List<T>,
+}
+
+impl<T: Clone> Zipper<T> {
+    pub fn new() -> Self {
+        Zipper {
+            left: List::new(),
+            right: List::new(),
+        }
+    }
+
+    //Focus on the first element, nothing gets copied
+    pub fn from_list(list: &List<T>) -> Self {
+        Zipper {
+            left: List::new(),
+            right: list.clone(),
+        }
+    }
+
+    //Copies what is left of the focus back in front of the rest, which is shared
+    pub fn to_list(&self) -> List<T> {
+        self.left
+            .iter()
+            .fold(self.right.clone(), |list, elem| list.append(elem.clone()))
+    }
+
+    pub fn focus(&self) -> Option<&T> {
+        self.right.head()
+    }
+
+    //Number of elements left of the focus
+    pub fn position(&self) -> usize {
+        self.left.len()
+    }
+
+    //None if there is nothing further left
+    pub fn left(&self) -> Option<Self> {
+        let elem = self.left.head()?.clone();
+        Some(Zipper {
+            left: self.left.tail(),
+            right: self.right.append(elem),
+        })
+    }
+
+    //None if the focus is already past the last element
+    pub fn right(&self) -> Option<Self> {
+        let elem = self.right.head()?.clone();
+        Some(Zipper {
+            left: self.left.append(elem),
+            right: self.right.tail(),
+        })
+    }
+
+    //None past the last element, there is nothing to replace
+    pub fn replace(&self, elem: T) -> Option<Self> {
+        self.focus()?;
+        Some(Zipper {
+            left: self.left.clone(),
+            right: self.right.tail().append(elem),
+        })
+    }
+
+    //The new element takes the focus, the old focus moves one to the right
+    pub fn insert(&self, elem: T) -> Self {
+        Zipper {
+            left: self.left.clone(),
+            right: self.right.append(elem),
+        }
+    }
+
+    //The element after the old focus takes the focus. None past the last element
+    pub fn delete(&self) -> Option<Self> {
+        self.focus()?;
+        Some(Zipper {
+            left: self.left.clone(),
+            right: self.right.tail(),
+        })
+    }
+}
+
+impl<T> Clone for Zipper<T> {
+    fn clone(&self) -> Self {
+        Zipper {
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Default for Zipper<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Zipper;
+    use crate::third::List;
+
+    fn list(items: &[i32]) -> List<i32> {
+        items
+            .iter()
+            .rev()
+            .fold(List::new(), |list, &x| list.append(x))
+    }
+
+    fn items(zipper: &Zipper<i32>) -> Vec<i32> {
+        zipper.to_list().iter().cloned().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let zipper = Zipper::from_list(&list(&[1, 2, 3]));
+        assert_eq!(zipper.focus(), Some(&1));
+        assert!(zipper.left().is_none());
+
+        let zipper = zipper.right().unwrap();
+        assert_eq!(zipper.focus(), Some(&2));
+        assert_eq!(zipper.position(), 1);
+
+        let end = zipper.right().unwrap().right().unwrap();
+        assert_eq!(end.focus(), None);
+        assert!(end.right().is_none());
+        assert!(end.replace(9).is_none());
+        assert!(end.delete().is_none());
+        assert_eq!(items(&end.insert(4)), vec![1, 2, 3, 4]);
+        assert_eq!(end.left().unwrap().focus(), Some(&3));
+
+        assert_eq!(items(&zipper.replace(20).unwrap()), vec![1, 20, 3]);
+        assert_eq!(items(&zipper.insert(15)), vec![1, 15, 2, 3]);
+        assert_eq!(zipper.insert(15).focus(), Some(&15));
+        let deleted = zipper.delete().unwrap();
+        assert_eq!(deleted.focus(), Some(&3));
+        assert_eq!(items(&deleted), vec![1, 3]);
+        assert_eq!(items(&deleted.delete().unwrap()), vec![1]);
+
+        let empty = Zipper::new();
+        assert_eq!(empty.focus(), None);
+        assert_eq!(items(&empty.insert(1)), vec![1]);
+    }
+
+    #[test]
+    fn persistence() {
+        let start = Zipper::from_list(&list(&[1, 2, 3, 4]));
+        let middle = start.right().unwrap().right().unwrap();
+        let edited = middle
+            .replace(30)
+            .unwrap()
+            .insert(25)
+            .left()
+            .unwrap()
+            .delete()
+            .unwrap();
+        assert_eq!(items(&edited), vec![1, 25, 30, 4]);
+        //Nothing before it changed
+        assert_eq!(items(&start), vec![1, 2, 3, 4]);
+        assert_eq!(items(&middle), vec![1, 2, 3, 4]);
+        assert_eq!(middle.focus(), Some(&3));
+
+        //Converting back shares everything from the focus on
+        let original = list(&[1, 2, 3, 4]);
+        let zipper = Zipper::from_list(&original);
+        assert!(zipper.to_list().ptr_eq(&original));
+        let moved = zipper.right().unwrap().right().unwrap();
+        assert_eq!(moved.to_list().shared_suffix_len(&original), 2);
+        assert_eq!(
+            moved
+                .replace(0)
+                .unwrap()
+                .to_list()
+                .shared_suffix_len(&original),
+            1
+        );
+    }
+
+    #[test]
+    fn long() {
+        let n = 1_000_000;
+        let mut zipper = Zipper::new();
+        for i in 0..n {
+            zipper = zipper.insert(i).right().unwrap();
+        }
+        assert_eq!(zipper.position(), n as usize);
+        for _ in 0..n / 2 {
+            zipper = zipper.left().unwrap();
+        }
+        assert_eq!(zipper.focus(), Some(&(n / 2)));
+        assert_eq!(zipper.to_list().len(), n as usize);
+    }
+}